@@ -7,7 +7,7 @@ const ACTION_CATEGORY_ID: &str = "app.category.action";
 const TEXT_INPUT_CATEGORY_ID: &str = "app.category.textinput";
 
 fn init_logger() {
-    let _ = env_logger::Builder::from_default_env()
+    env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
         .is_test(false)
         .init();
@@ -35,6 +35,8 @@ fn create_test_categories() -> Vec<NotifyCategory> {
                     title: "Detail".to_string(),
                 },
             ],
+            send_dismiss_action: true,
+            ..Default::default()
         },
         NotifyCategory {
             identifier: TEXT_INPUT_CATEGORY_ID.to_string(),
@@ -44,6 +46,7 @@ fn create_test_categories() -> Vec<NotifyCategory> {
                 input_button_title: "Send".to_string(),
                 input_placeholder: "Type your message here...".to_string(),
             }],
+            ..Default::default()
         },
     ]
 }
@@ -256,20 +256,22 @@ async fn test_tauri_style_multiple_threads() -> Result<(), Box<dyn std::error::E
     }
 
     // Check all results
-    let final_results = results.lock().unwrap();
-    let success_count = final_results.iter().filter(|(_, result)| *result).count();
-
-    println!(
-        "📊 Multi-worker test completed: {}/{} workers succeeded",
-        success_count,
-        final_results.len()
-    );
-
-    for (worker_id, result) in final_results.iter() {
-        if *result {
-            println!("✅ Worker {worker_id} passed");
-        } else {
-            println!("❌ Worker {worker_id} failed");
+    {
+        let final_results = results.lock().unwrap();
+        let success_count = final_results.iter().filter(|(_, result)| *result).count();
+
+        println!(
+            "📊 Multi-worker test completed: {}/{} workers succeeded",
+            success_count,
+            final_results.len()
+        );
+
+        for (worker_id, result) in final_results.iter() {
+            if *result {
+                println!("✅ Worker {worker_id} passed");
+            } else {
+                println!("❌ Worker {worker_id} failed");
+            }
         }
     }
 
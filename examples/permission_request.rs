@@ -20,6 +20,7 @@ fn create_test_categories() -> Vec<NotifyCategory> {
                 title: "Cancel".to_string(),
             },
         ],
+        ..Default::default()
     }]
 }
 
@@ -43,9 +44,15 @@ async fn main() -> anyhow::Result<()> {
     // Request permission
     #[cfg(target_os = "macos")]
     {
-        println!("🔐 Requesting notification permission...");
-        match manager.first_time_ask_for_notification_permission().await {
-            Ok(_) => println!("✅ Permission request completed successfully"),
+        // `--provisional` delivers quietly without showing the permission prompt
+        let options = if std::env::args().any(|arg| arg == "--provisional") {
+            PermissionOptions::provisional()
+        } else {
+            PermissionOptions::default()
+        };
+        println!("🔐 Requesting notification permission with {options:?}...");
+        match manager.request_permission(options).await {
+            Ok(state) => println!("✅ Permission request completed: {state:?}"),
             Err(err) => {
                 println!("❌ Permission request failed: {err:?}");
                 return Err(err.into());
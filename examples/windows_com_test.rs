@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use tokio::time::{sleep, Duration};
 use user_notify_reborn::prelude::*;
 
@@ -37,6 +36,7 @@ async fn main() -> anyhow::Result<()> {
             identifier: "test.action".to_string(),
             title: "Click Me".to_string(),
         }],
+        ..Default::default()
     }];
 
     manager.register(
@@ -0,0 +1,475 @@
+//! Deduplication of identical notifications
+//!
+//! [`DedupeNotifyManager`] wraps a manager and detects notifications that duplicate one it
+//! sent within a time window, e.g. a "Connection lost" notification of a reconnect loop.
+//! Duplicates are detected by [`NotifyBuilder::dedupe_key`] if set, else by title, subtitle,
+//! body, category and thread. Depending on the [`DedupePolicy`] a duplicate is suppressed or
+//! replaces the notification it duplicates.
+//!
+//! The window starts when a notification is sent or replaced, suppressed duplicates do not
+//! extend it. Notifications without an id are sent with a random one, so that duplicates sent
+//! while the notification is still being sent are detected too. If sending fails, the
+//! duplicates suppressed meanwhile are not sent either.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use crate::{
+    DeliveredContent, Error, NotificationSettings, NotifyBuilder, NotifyCategory, NotifyHandleExt,
+    NotifyManagerExt, NotifyResponse, PermissionChanges, PermissionOptions, PermissionState,
+    PresentationOptions, PresentedNotification,
+};
+
+/// What happens to a duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeAction {
+    /// The duplicate is not sent
+    Suppress,
+    /// The duplicate is sent with the id of the notification it duplicates, so that it
+    /// replaces it, or the notification is removed if the duplicate has its own id. The
+    /// duplicate is sent even if the removal fails.
+    Replace,
+}
+
+/// How duplicates within a time window are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DedupePolicy {
+    window: Duration,
+    action: DedupeAction,
+}
+
+impl DedupePolicy {
+    pub fn new(window: Duration, action: DedupeAction) -> Self {
+        Self { window, action }
+    }
+
+    /// Suppress duplicates within the window
+    pub fn suppress(window: Duration) -> Self {
+        Self::new(window, DedupeAction::Suppress)
+    }
+
+    /// Replace the notification with its duplicates within the window
+    pub fn replace(window: Duration) -> Self {
+        Self::new(window, DedupeAction::Replace)
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn action(&self) -> DedupeAction {
+        self.action
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DedupeKey {
+    Explicit(String),
+    Content(u64),
+}
+
+impl DedupeKey {
+    fn of(builder: &NotifyBuilder) -> Self {
+        if let Some(dedupe_key) = &builder.dedupe_key {
+            return DedupeKey::Explicit(dedupe_key.clone());
+        }
+        let mut hasher = DefaultHasher::new();
+        (
+            &builder.title,
+            &builder.subtitle,
+            &builder.body,
+            &builder.category_id,
+            &builder.thread_id,
+        )
+            .hash(&mut hasher);
+        DedupeKey::Content(hasher.finish())
+    }
+}
+
+#[derive(Debug)]
+struct SentNotification {
+    id: String,
+    sent_at: Instant,
+}
+
+/// Key reserved for a notification before it is sent
+#[derive(Debug)]
+struct Reservation {
+    /// Id to send the notification with
+    id: String,
+    sent_at: Instant,
+    /// Notification that is replaced
+    replaced: Option<SentNotification>,
+}
+
+/// Outcome of [`DedupeNotifyManager::reserve`]
+enum Check {
+    /// Duplicate of the notification with the id
+    Suppress(String),
+    Send(Reservation),
+}
+
+/// Manager that suppresses or replaces duplicate notifications
+#[derive(Debug)]
+pub struct DedupeNotifyManager<M> {
+    inner: M,
+    policy: DedupePolicy,
+    sent: Arc<Mutex<HashMap<DedupeKey, SentNotification>>>,
+}
+
+impl<M: NotifyManagerExt> DedupeNotifyManager<M> {
+    pub fn new(inner: M, policy: DedupePolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            sent: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the wrapped manager
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> DedupePolicy {
+        self.policy
+    }
+
+    /// Forgets the sent notifications, so that the next notification of every key is sent
+    pub fn reset(&self) -> Result<(), Error> {
+        self.lock()?.clear();
+        Ok(())
+    }
+
+    /// Checks whether `key` duplicates a notification within the window and, unless the
+    /// duplicate is suppressed, reserves `key` for the notification before it is sent
+    ///
+    /// The notification is sent with `id`, else with the id of the notification it replaces,
+    /// else with a random id.
+    fn reserve(&self, key: &DedupeKey, id: Option<&str>) -> Result<Check, Error> {
+        let mut sent = self.lock()?;
+        let now = Instant::now();
+        sent.retain(|_, notification| {
+            now.duration_since(notification.sent_at) < self.policy.window
+        });
+
+        let duplicate_of = sent.get(key).map(|notification| notification.id.clone());
+        let id = match (duplicate_of, self.policy.action) {
+            (Some(duplicate_of), DedupeAction::Suppress) => {
+                return Ok(Check::Suppress(duplicate_of))
+            }
+            (Some(replaced_id), DedupeAction::Replace) => id.map_or(replaced_id, str::to_string),
+            (None, _) => id.map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string),
+        };
+        let replaced = sent.insert(
+            key.clone(),
+            SentNotification {
+                id: id.clone(),
+                sent_at: now,
+            },
+        );
+        Ok(Check::Send(Reservation {
+            id,
+            sent_at: now,
+            replaced,
+        }))
+    }
+
+    /// Undoes the reservation of a notification that failed to send
+    fn release(&self, key: DedupeKey, reservation: Reservation) {
+        let Ok(mut sent) = self.lock() else {
+            return;
+        };
+        // a later notification may have taken the key meanwhile
+        let reserved = sent.get(&key).is_some_and(|notification| {
+            notification.id == reservation.id && notification.sent_at == reservation.sent_at
+        });
+        if reserved {
+            match reservation.replaced {
+                Some(replaced) => sent.insert(key, replaced),
+                None => sent.remove(&key),
+            };
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<DedupeKey, SentNotification>>, Error> {
+        self.sent
+            .lock()
+            .map_err(|_| Error::Other("dedupe lock poisoned".to_string()))
+    }
+}
+
+/// Handle of [`DedupeNotifyManager`]
+#[derive(Debug)]
+pub enum DedupeNotifyHandle<H> {
+    /// Sent, no duplicate within the window
+    Sent(H),
+    /// Sent in place of the notification it duplicates
+    Replaced { handle: H, replaced_id: String },
+    /// Not sent, as it duplicates a notification within the window
+    Suppressed(Box<SuppressedNotifyHandle>),
+}
+
+impl<H: NotifyHandleExt> DedupeNotifyHandle<H> {
+    /// Returns the handle of the wrapped manager if the notification was sent
+    pub fn sent(&self) -> Option<&H> {
+        match self {
+            DedupeNotifyHandle::Sent(handle) | DedupeNotifyHandle::Replaced { handle, .. } => {
+                Some(handle)
+            }
+            DedupeNotifyHandle::Suppressed(_) => None,
+        }
+    }
+
+    pub fn is_suppressed(&self) -> bool {
+        matches!(self, DedupeNotifyHandle::Suppressed(_))
+    }
+
+    /// Returns the id of the notification that this one duplicates
+    pub fn duplicate_of(&self) -> Option<&str> {
+        match self {
+            DedupeNotifyHandle::Sent(_) => None,
+            DedupeNotifyHandle::Replaced { replaced_id, .. } => Some(replaced_id),
+            DedupeNotifyHandle::Suppressed(handle) => Some(&handle.duplicate_of),
+        }
+    }
+
+    fn handle(&self) -> &dyn NotifyHandleExt {
+        match self {
+            DedupeNotifyHandle::Sent(handle) | DedupeNotifyHandle::Replaced { handle, .. } => {
+                handle
+            }
+            DedupeNotifyHandle::Suppressed(handle) => handle.as_ref(),
+        }
+    }
+}
+
+impl<H: NotifyHandleExt> NotifyHandleExt for DedupeNotifyHandle<H> {
+    fn close(&self) -> Result<(), Error> {
+        self.handle().close()
+    }
+
+    fn get_id(&self) -> String {
+        self.handle().get_id()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.handle().title()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.handle().subtitle()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.handle().body()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.handle().category_id()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.handle().thread_id()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        self.handle().user_metadata()
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        self.handle().delivered_at()
+    }
+}
+
+/// Notification that was suppressed as a duplicate
+///
+/// Its id is the id of the notification it duplicates.
+#[derive(Debug)]
+pub struct SuppressedNotifyHandle {
+    duplicate_of: String,
+    content: DeliveredContent,
+}
+
+impl NotifyHandleExt for SuppressedNotifyHandle {
+    /// Does nothing, the notification was never shown
+    fn close(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_id(&self) -> String {
+        self.duplicate_of.clone()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    /// `None`, the notification was never delivered
+    fn delivered_at(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+#[async_trait]
+impl<M: NotifyManagerExt> NotifyManagerExt for DedupeNotifyManager<M> {
+    type NotifyHandle = DedupeNotifyHandle<M::NotifyHandle>;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        self.inner.get_notification_permission_state().await
+    }
+
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
+        self.inner.get_notification_settings().await
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error> {
+        self.inner
+            .first_time_ask_for_notification_permission()
+            .await
+    }
+
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.inner.request_permission(options).await
+    }
+
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        self.inner.permission_changes().await
+    }
+
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        self.inner.set_badge(count).await
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.inner.get_badge()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.inner.set_should_present(should_present)
+    }
+
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error> {
+        self.inner.set_foreground(foreground)
+    }
+
+    fn register(
+        &self,
+        handler_callback: Box<dyn Fn(NotifyResponse) + Send + Sync + 'static>,
+        categories: Vec<NotifyCategory>,
+    ) -> Result<(), Error> {
+        self.inner.register(handler_callback, categories)
+    }
+
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        self.inner.set_categories(categories)
+    }
+
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        self.inner.take_launch_response(args)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), Error> {
+        self.inner.remove_all_delivered_notifications()
+    }
+
+    fn remove_delivered_notifications(&self, ids: Vec<&str>) -> Result<(), Error> {
+        self.inner.remove_delivered_notifications(ids)
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, Error> {
+        Ok(self
+            .inner
+            .get_active_notifications()
+            .await?
+            .into_iter()
+            .map(DedupeNotifyHandle::Sent)
+            .collect())
+    }
+
+    async fn send(&self, mut builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
+        let key = DedupeKey::of(&builder);
+        let reservation = match self.reserve(&key, builder.id.as_deref())? {
+            Check::Suppress(duplicate_of) => {
+                log::debug!("suppressing duplicate of notification {duplicate_of}");
+                let mut content = DeliveredContent::from_builder(&builder);
+                content.delivered_at = None;
+                return Ok(DedupeNotifyHandle::Suppressed(Box::new(
+                    SuppressedNotifyHandle {
+                        duplicate_of,
+                        content,
+                    },
+                )));
+            }
+            Check::Send(reservation) => reservation,
+        };
+
+        builder.id = Some(reservation.id.clone());
+        let sent: Result<M::NotifyHandle, Error> = async {
+            // a duplicate with its own id does not replace the notification by its id
+            if let Some(replaced) = &reservation.replaced {
+                if replaced.id != reservation.id {
+                    // e.g. already dismissed, the replacement is sent anyway
+                    if let Err(err) = self
+                        .inner
+                        .remove_delivered_notifications(vec![replaced.id.as_str()])
+                    {
+                        log::warn!(
+                            "failed to remove replaced notification {}: {err}",
+                            replaced.id
+                        );
+                    }
+                }
+            }
+            self.inner.send(builder).await
+        }
+        .await;
+
+        match sent {
+            Ok(handle) => Ok(match reservation.replaced {
+                Some(replaced) => DedupeNotifyHandle::Replaced {
+                    handle,
+                    replaced_id: replaced.id,
+                },
+                None => DedupeNotifyHandle::Sent(handle),
+            }),
+            Err(err) => {
+                self.release(key, reservation);
+                Err(err)
+            }
+        }
+    }
+}
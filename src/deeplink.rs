@@ -0,0 +1,257 @@
+//! Signed deeplinks for protocol activation of notifications
+//!
+//! When a notification protocol is configured, clicking a notification launches
+//! `{scheme}://{notification_id}/{action}?v=1&m={metadata}&s={signature}`.
+//! Any application can invoke the scheme, so every link carries an HMAC-SHA256
+//! signature keyed by an app-provided secret and links that fail verification
+//! are rejected with [`Error::DeeplinkSignatureMismatch`].
+//!
+//! The codec is available on every platform, so that e.g. a single-instance handler
+//! can [`decode`] the links Windows passes to a newly launched process through argv.
+//!
+//! - `notification_id` and `action` are percent-encoded, `__default__`, `__dismiss__`,
+//!   `__expired__`, `__hidden__` and `__open_url__:{url}` are the actions of
+//!   [`NotifyResponseAction`] variants
+//! - `metadata` is the JSON user metadata in URL-safe Base64 without padding
+//! - `signature` is the HMAC in URL-safe Base64 without padding
+
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::Sha256;
+
+use crate::{Error, NotifyResponse, NotifyResponseAction};
+
+/// Version of the deeplink format produced by [`encode`]
+pub const DEEPLINK_VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "v";
+const METADATA_KEY: &str = "m";
+const SIGNATURE_KEY: &str = "s";
+
+const DEFAULT_ACTION: &str = "__default__";
+const DISMISS_ACTION: &str = "__dismiss__";
+const EXPIRED_ACTION: &str = "__expired__";
+const HIDDEN_ACTION: &str = "__hidden__";
+const OPEN_URL_ACTION_PREFIX: &str = "__open_url__:";
+
+/// Everything but the unreserved characters of RFC 3986
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Encodes a custom protocol deeplink for notification activation.
+///
+/// This creates a URL that can be used to handle notification responses through a custom protocol.
+/// The user metadata is Base64-encoded to safely include complex data in the URL, and the link is
+/// signed with `secret` so that [`decode`] can detect forged or tampered links.
+///
+/// # References
+/// - [Launch your app with a URI](https://docs.microsoft.com/en-us/windows/uwp/launch-resume/launch-app-with-uri)
+/// - [Handle app activation](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/send-local-toast-desktop)
+/// - [Base64 URL-safe alphabet (RFC 4648 §5)](https://tools.ietf.org/html/rfc4648#section-5)
+/// - [Percent-encoding (RFC 3986 §2.1)](https://tools.ietf.org/html/rfc3986#section-2.1)
+/// - [HMAC (RFC 2104)](https://tools.ietf.org/html/rfc2104)
+pub fn encode(scheme: &str, response: &NotifyResponse, secret: &[u8]) -> String {
+    let metadata = BASE64_URL_SAFE_NO_PAD.encode(response.user_metadata.to_string());
+
+    let action = action_id(&response.action);
+
+    let signature = BASE64_URL_SAFE_NO_PAD.encode(sign(
+        secret,
+        DEEPLINK_VERSION,
+        &response.notification_id,
+        &action,
+        &metadata,
+    ));
+
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair(VERSION_KEY, &DEEPLINK_VERSION.to_string())
+        .append_pair(METADATA_KEY, &metadata)
+        .append_pair(SIGNATURE_KEY, &signature)
+        .finish();
+
+    format!(
+        "{scheme}://{}/{}?{query}",
+        utf8_percent_encode(&response.notification_id, COMPONENT),
+        utf8_percent_encode(&action, COMPONENT)
+    )
+}
+
+/// Decodes a custom protocol deeplink back into a NotifyResponse.
+///
+/// This function parses URLs created by [`encode`] to extract notification
+/// response information when the application is activated through the protocol handler.
+///
+/// # Errors
+/// - `Error::InvalidDeeplink` if the link is missing parts or has an unsupported version
+/// - `Error::DeeplinkSignatureMismatch` if the link was not signed with `secret`
+///
+/// # References
+/// - [url crate documentation](https://docs.rs/url/latest/url/)
+/// - [URL parsing specification (RFC 3986)](https://tools.ietf.org/html/rfc3986)
+/// - [Base64 URL-safe alphabet (RFC 4648 §5)](https://tools.ietf.org/html/rfc4648#section-5)
+pub fn decode(link: &str, secret: &[u8]) -> Result<NotifyResponse, Error> {
+    let url = url::Url::parse(link)?;
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    let query_value = |key: &str| {
+        query
+            .get(key)
+            .ok_or_else(|| Error::InvalidDeeplink(format!("missing `{key}` query parameter")))
+    };
+
+    let version = query_value(VERSION_KEY)?;
+    if version.parse::<u32>().ok() != Some(DEEPLINK_VERSION) {
+        return Err(Error::InvalidDeeplink(format!(
+            "unsupported version `{version}`"
+        )));
+    }
+
+    let notification_id = percent_decode_component(url.host_str().unwrap_or_default())?;
+    let action = percent_decode_component(url.path().strip_prefix('/').unwrap_or_default())?;
+    let metadata = query_value(METADATA_KEY)?;
+    let signature = BASE64_URL_SAFE_NO_PAD.decode(query_value(SIGNATURE_KEY)?)?;
+
+    verify(
+        secret,
+        DEEPLINK_VERSION,
+        &notification_id,
+        &action,
+        metadata,
+        &signature,
+    )?;
+
+    let user_metadata: serde_json::Value = {
+        let user_info_str = BASE64_URL_SAFE_NO_PAD.decode(metadata)?;
+        serde_json::from_slice(user_info_str.as_slice()).map_err(Error::FailedToParseUserInfo)?
+    };
+
+    Ok(NotifyResponse {
+        notification_id,
+        action: action_from_id(action),
+        user_input: None,
+        user_metadata,
+    })
+}
+
+/// Finds and decodes the notification deeplink among the arguments of a process.
+///
+/// When a notification is clicked after the app has exited, Windows launches the app with
+/// the deeplink of the `notification_protocol` as an argument. Arguments that are not a
+/// link of `scheme` are ignored.
+///
+/// # Errors
+/// - The errors of [`decode`] for a link of `scheme` that fails to decode or verify
+///
+/// # References
+/// - [Handle URI activation](https://docs.microsoft.com/en-us/windows/uwp/launch-resume/handle-uri-activation)
+pub fn decode_launch_args(
+    scheme: &str,
+    args: &[String],
+    secret: &[u8],
+) -> Result<Option<NotifyResponse>, Error> {
+    let prefix = format!("{scheme}://");
+    args.iter()
+        .find(|arg| {
+            arg.get(..prefix.len())
+                .is_some_and(|arg_prefix| arg_prefix.eq_ignore_ascii_case(&prefix))
+        })
+        .map(|link| decode(link, secret))
+        .transpose()
+}
+
+/// Returns the action id of a response action
+pub(crate) fn action_id(action: &NotifyResponseAction) -> Cow<'_, str> {
+    match action {
+        NotifyResponseAction::Default => DEFAULT_ACTION.into(),
+        NotifyResponseAction::Dismiss => DISMISS_ACTION.into(),
+        NotifyResponseAction::Expired => EXPIRED_ACTION.into(),
+        NotifyResponseAction::Hidden => HIDDEN_ACTION.into(),
+        NotifyResponseAction::OpenUrl(url) => format!("{OPEN_URL_ACTION_PREFIX}{url}").into(),
+        NotifyResponseAction::Other(action) => action.into(),
+    }
+}
+
+/// Returns the response action of an action id, ids that are not reserved are
+/// [`NotifyResponseAction::Other`]
+pub(crate) fn action_from_id(action: String) -> NotifyResponseAction {
+    match action.as_str() {
+        DEFAULT_ACTION => NotifyResponseAction::Default,
+        DISMISS_ACTION => NotifyResponseAction::Dismiss,
+        EXPIRED_ACTION => NotifyResponseAction::Expired,
+        HIDDEN_ACTION => NotifyResponseAction::Hidden,
+        _ => match action
+            .strip_prefix(OPEN_URL_ACTION_PREFIX)
+            .and_then(|url| url::Url::parse(url).ok())
+        {
+            Some(url) => NotifyResponseAction::OpenUrl(url),
+            None => NotifyResponseAction::Other(action),
+        },
+    }
+}
+
+/// Generates a random secret for managers that were not given one
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub(crate) fn generate_secret() -> Arc<[u8]> {
+    [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()]
+        .iter()
+        .flat_map(|uuid| *uuid.as_bytes())
+        .collect()
+}
+
+/// Decodes a percent-encoded host or path component of a deeplink
+fn percent_decode_component(component: &str) -> Result<String, Error> {
+    percent_decode_str(component)
+        .decode_utf8()
+        .map(|component| component.into_owned())
+        .map_err(|err| Error::InvalidDeeplink(format!("`{component}` is not UTF-8: {err}")))
+}
+
+/// Creates the MAC over every part of the link that ends up in the response
+fn mac(
+    secret: &[u8],
+    version: u32,
+    notification_id: &str,
+    action: &str,
+    metadata: &str,
+) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take a key of any size");
+    for part in [&version.to_string(), notification_id, action, metadata] {
+        // length-prefix every part so that no two distinct links share a message
+        mac.update(&(part.len() as u64).to_be_bytes());
+        mac.update(part.as_bytes());
+    }
+    mac
+}
+
+fn sign(
+    secret: &[u8],
+    version: u32,
+    notification_id: &str,
+    action: &str,
+    metadata: &str,
+) -> Vec<u8> {
+    mac(secret, version, notification_id, action, metadata)
+        .finalize()
+        .into_bytes()
+        .to_vec()
+}
+
+fn verify(
+    secret: &[u8],
+    version: u32,
+    notification_id: &str,
+    action: &str,
+    metadata: &str,
+    signature: &[u8],
+) -> Result<(), Error> {
+    mac(secret, version, notification_id, action, metadata)
+        .verify_slice(signature)
+        .map_err(|_| Error::DeeplinkSignatureMismatch)
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum Error {
     // macOS errors
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     #[error("bundle id is not set, this is required to send notifications")]
     NoBundleId,
     #[cfg(target_os = "macos")]
@@ -30,25 +30,19 @@ pub enum Error {
     #[error(transparent)]
     Windows(#[from] windows::core::Error),
     #[cfg(target_os = "windows")]
-    #[error("Failed to parse user info {0:?}")]
-    FailedToParseUserInfo(serde_json::Error),
-    #[cfg(target_os = "windows")]
     #[error("Error Setting Handler Callback")]
     SettingHandler,
     #[cfg(target_os = "windows")]
     #[error(transparent)]
     XmlEscape(#[from] quick_xml::escape::EscapeError),
-    #[cfg(target_os = "windows")]
-    #[error(transparent)]
-    UrlParse(#[from] url::ParseError),
-    #[cfg(target_os = "windows")]
-    #[error(transparent)]
-    Base64Decode(#[from] base64::DecodeError),
 
     // Linux errors
     #[cfg(target_os = "linux")]
     #[error("Notifications are not supported on this platform")]
     NotSupported,
+    #[cfg(target_os = "linux")]
+    #[error(transparent)]
+    DBus(#[from] zbus::Error),
 
     // Common errors
     #[error("Infallible error, something went really wrong: {0}")]
@@ -57,13 +51,42 @@ pub enum Error {
     TokioRecv(#[from] tokio::sync::oneshot::error::RecvError),
     #[error(transparent)]
     TokioTryLock(#[from] tokio::sync::TryLockError),
+    #[error("Failed to parse user info {0:?}")]
+    FailedToParseUserInfo(serde_json::Error),
+    #[error("Failed to serialize user metadata {0:?}")]
+    FailedToSerializeUserMetadata(serde_json::Error),
+    #[error("Failed to serialize history event {0:?}")]
+    FailedToSerializeHistory(serde_json::Error),
+    #[error(transparent)]
+    UrlParse(#[from] url::ParseError),
+    #[error(transparent)]
+    Base64Decode(#[from] base64::DecodeError),
+    #[error("Invalid notification deeplink: {0}")]
+    InvalidDeeplink(String),
+    #[error("Notification deeplink signature mismatch, the link was forged or tampered with")]
+    DeeplinkSignatureMismatch,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse notification templates: {0}")]
+    TemplateParse(String),
+    #[error("Notification template not found: {0}")]
+    TemplateNotFound(String),
+    #[error("Invalid notification template `{template}`: {reason}")]
+    InvalidTemplate { template: String, reason: String },
+    #[error("Notification template `{template}` is missing variable `{variable}`")]
+    MissingTemplateVariable { template: String, variable: String },
+    #[error("Failed to parse localization resource: {0}")]
+    LocalizationParse(String),
+    #[error("Invalid body markup: {0}")]
+    InvalidMarkup(String),
+    #[error("Invalid notification id `{id}`: {reason}")]
+    InvalidNotificationId { id: String, reason: String },
     #[error("Url from path parse error {0:?}")]
     ParseUrlFromPath(PathBuf),
     #[error("Other error: {0}")]
     Other(String),
 }
 
-
 #[cfg(target_os = "macos")]
 impl From<&objc2_foundation::NSError> for Error {
     fn from(error: &objc2_foundation::NSError) -> Self {
@@ -0,0 +1,555 @@
+//! Persistent history of sent notifications and their responses
+//!
+//! [`NotificationHistory`] appends every event as a JSON line to a file, so that an in-app
+//! notification center can list past notifications after the system removed them.
+//! [`HistoryNotifyManager`] wraps a manager and records everything it sends and receives.
+//! [`HistoryRetention`] limits how many notifications, and how old ones, are kept.
+//!
+//! ```jsonl
+//! {"type":"sent","notification_id":"…","timestamp_ms":1760000000000,"title":"Build finished",…}
+//! {"type":"response","notification_id":"…","timestamp_ms":1760000004200,"action":"__default__",…}
+//! ```
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deeplink, Error, NotificationSettings, NotifyBuilder, NotifyCategory, NotifyHandleExt,
+    NotifyManagerExt, NotifyResponse, NotifyResponseAction, PermissionChanges, PermissionOptions,
+    PermissionState, PresentationOptions, PresentedNotification,
+};
+
+/// A line of the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HistoryEvent {
+    Sent {
+        notification_id: String,
+        timestamp_ms: u64,
+        title: Option<String>,
+        subtitle: Option<String>,
+        body: Option<String>,
+        category_id: Option<String>,
+        thread_id: Option<String>,
+        #[serde(default)]
+        user_metadata: serde_json::Value,
+    },
+    Response {
+        notification_id: String,
+        timestamp_ms: u64,
+        /// Action id as in [crate::deeplink]
+        action: String,
+        user_input: Option<String>,
+    },
+}
+
+/// A sent notification with the responses it received
+#[derive(Debug, Clone)]
+pub struct HistoryItem {
+    pub notification_id: String,
+    pub sent_at: SystemTime,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: Option<String>,
+    pub category_id: Option<String>,
+    pub thread_id: Option<String>,
+    /// Metadata of the notification, `null` if none was set
+    pub user_metadata: serde_json::Value,
+    /// Responses and dismissals in the order they were received
+    pub responses: Vec<HistoryResponse>,
+}
+
+/// Interaction of the user with a notification of the history
+#[derive(Debug, Clone)]
+pub struct HistoryResponse {
+    pub received_at: SystemTime,
+    pub action: NotifyResponseAction,
+    pub user_input: Option<String>,
+}
+
+/// Filter of [`NotificationHistory::query`]
+///
+/// Matches notifications that were sent in the time range and have the thread and category,
+/// if set.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    thread_id: Option<String>,
+    category_id: Option<String>,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+    limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn thread_id(mut self, thread_id: &str) -> Self {
+        self.thread_id = Some(thread_id.to_string());
+        self
+    }
+
+    pub fn category_id(mut self, category_id: &str) -> Self {
+        self.category_id = Some(category_id.to_string());
+        self
+    }
+
+    /// Only notifications sent at or after the time
+    pub fn since(mut self, since: SystemTime) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only notifications sent before the time
+    pub fn until(mut self, until: SystemTime) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Return at most the latest `limit` notifications
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn matches(&self, item: &HistoryItem) -> bool {
+        self.thread_id
+            .as_ref()
+            .is_none_or(|thread_id| item.thread_id.as_ref() == Some(thread_id))
+            && self
+                .category_id
+                .as_ref()
+                .is_none_or(|category_id| item.category_id.as_ref() == Some(category_id))
+            && self.since.is_none_or(|since| item.sent_at >= since)
+            && self.until.is_none_or(|until| item.sent_at < until)
+    }
+}
+
+/// How much of the history is kept, see [`NotificationHistory::with_retention`]
+///
+/// Everything is kept by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryRetention {
+    /// Keep at most this many notifications, the oldest are removed first
+    pub max_entries: Option<usize>,
+    /// Remove notifications that were sent longer ago
+    pub max_age: Option<Duration>,
+}
+
+impl HistoryRetention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct HistoryInner {
+    file: File,
+    /// Number of events in the file, including those of removed notifications
+    lines: usize,
+    items: Vec<HistoryItem>,
+    retention: HistoryRetention,
+}
+
+impl HistoryInner {
+    /// Removes the notifications that the retention does not keep
+    fn prune(&mut self, now: SystemTime) {
+        if let Some(oldest) = self
+            .retention
+            .max_age
+            .and_then(|max_age| now.checked_sub(max_age))
+        {
+            self.items.retain(|item| item.sent_at >= oldest);
+        }
+        if let Some(max_entries) = self.retention.max_entries {
+            let excess = self.items.len().saturating_sub(max_entries);
+            self.items.drain(..excess);
+        }
+    }
+
+    /// Rewrites the file with the kept notifications once it holds twice their events
+    fn compact(&mut self, path: &Path) -> Result<(), Error> {
+        let kept: usize = self.items.iter().map(|item| 1 + item.responses.len()).sum();
+        if self.lines <= 2 * kept {
+            return Ok(());
+        }
+
+        let mut contents = String::new();
+        for event in self.items.iter().flat_map(item_events) {
+            contents
+                .push_str(&serde_json::to_string(&event).map_err(Error::FailedToSerializeHistory)?);
+            contents.push('\n');
+        }
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, path)?;
+
+        self.file = OpenOptions::new().read(true).append(true).open(path)?;
+        self.lines = kept;
+        Ok(())
+    }
+}
+
+/// Notification history stored as JSON lines
+#[derive(Debug, Clone)]
+pub struct NotificationHistory {
+    path: PathBuf,
+    inner: Arc<Mutex<HistoryInner>>,
+}
+
+impl NotificationHistory {
+    /// Opens the history file, creating it if it does not exist
+    ///
+    /// Lines that cannot be parsed are skipped with a warning.
+    ///
+    /// # Errors
+    /// - `Error::Io` if the file cannot be read or created
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut items = Vec::new();
+        let mut lines = 0;
+        for (index, line) in BufReader::new(&file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            lines += 1;
+            match serde_json::from_str::<HistoryEvent>(&line) {
+                Ok(event) => apply_event(&mut items, event),
+                Err(err) => log::warn!("skipping history line {} of {path:?}: {err}", index + 1),
+            }
+        }
+
+        Ok(Self {
+            path,
+            inner: Arc::new(Mutex::new(HistoryInner {
+                file,
+                lines,
+                items,
+                retention: HistoryRetention::default(),
+            })),
+        })
+    }
+
+    /// Keeps only the notifications that the retention allows, removing the others now and
+    /// whenever an event is recorded
+    ///
+    /// The file is rewritten without the removed notifications once it holds twice the
+    /// events of the kept ones.
+    ///
+    /// # Errors
+    /// - `Error::Io` if the file cannot be rewritten
+    pub fn with_retention(self, retention: HistoryRetention) -> Result<Self, Error> {
+        {
+            let mut inner = self.lock()?;
+            inner.retention = retention;
+            inner.prune(SystemTime::now());
+            inner.compact(&self.path)?;
+        }
+        Ok(self)
+    }
+
+    /// Returns the path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a notification that was sent with the id
+    pub fn record_sent(&self, notification_id: &str, builder: &NotifyBuilder) -> Result<(), Error> {
+        self.append(HistoryEvent::Sent {
+            notification_id: notification_id.to_string(),
+            timestamp_ms: timestamp_ms(SystemTime::now()),
+            title: builder.title.clone(),
+            subtitle: builder.subtitle.clone(),
+            body: builder.body.clone(),
+            category_id: builder.category_id.clone(),
+            thread_id: builder.thread_id.clone(),
+            user_metadata: builder.user_metadata.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Records a response or dismissal
+    pub fn record_response(&self, response: &NotifyResponse) -> Result<(), Error> {
+        self.append(HistoryEvent::Response {
+            notification_id: response.notification_id.clone(),
+            timestamp_ms: timestamp_ms(SystemTime::now()),
+            action: deeplink::action_id(&response.action).into_owned(),
+            user_input: response.user_input.clone(),
+        })
+    }
+
+    /// Returns the matching notifications, latest first
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryItem>, Error> {
+        let inner = self.lock()?;
+        Ok(inner
+            .items
+            .iter()
+            .rev()
+            .filter(|item| query.matches(item))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    /// Returns the latest notification with the id, the one responses are recorded for
+    pub fn get(&self, notification_id: &str) -> Result<Option<HistoryItem>, Error> {
+        let inner = self.lock()?;
+        Ok(inner
+            .items
+            .iter()
+            .rev()
+            .find(|item| item.notification_id == notification_id)
+            .cloned())
+    }
+
+    /// Removes every notification from the history and truncates the file
+    pub fn clear(&self) -> Result<(), Error> {
+        let mut inner = self.lock()?;
+        inner.file.set_len(0)?;
+        inner.lines = 0;
+        inner.items.clear();
+        Ok(())
+    }
+
+    fn append(&self, event: HistoryEvent) -> Result<(), Error> {
+        let mut line = serde_json::to_string(&event).map_err(Error::FailedToSerializeHistory)?;
+        line.push('\n');
+
+        let mut inner = self.lock()?;
+        inner.file.write_all(line.as_bytes())?;
+        inner.file.flush()?;
+        inner.lines += 1;
+        apply_event(&mut inner.items, event);
+        inner.prune(SystemTime::now());
+        inner.compact(&self.path)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HistoryInner>, Error> {
+        self.inner
+            .lock()
+            .map_err(|_| Error::Other("history lock poisoned".to_string()))
+    }
+}
+
+fn apply_event(items: &mut Vec<HistoryItem>, event: HistoryEvent) {
+    match event {
+        HistoryEvent::Sent {
+            notification_id,
+            timestamp_ms,
+            title,
+            subtitle,
+            body,
+            category_id,
+            thread_id,
+            user_metadata,
+        } => items.push(HistoryItem {
+            notification_id,
+            sent_at: from_timestamp_ms(timestamp_ms),
+            title,
+            subtitle,
+            body,
+            category_id,
+            thread_id,
+            user_metadata,
+            responses: Vec::new(),
+        }),
+        HistoryEvent::Response {
+            notification_id,
+            timestamp_ms,
+            action,
+            user_input,
+        } => {
+            // responses to notifications sent before the history was enabled are dropped
+            if let Some(item) = items
+                .iter_mut()
+                .rev()
+                .find(|item| item.notification_id == notification_id)
+            {
+                item.responses.push(HistoryResponse {
+                    received_at: from_timestamp_ms(timestamp_ms),
+                    action: deeplink::action_from_id(action),
+                    user_input,
+                });
+            }
+        }
+    }
+}
+
+/// Returns the events that record the notification and its responses
+fn item_events(item: &HistoryItem) -> impl Iterator<Item = HistoryEvent> + '_ {
+    let sent = HistoryEvent::Sent {
+        notification_id: item.notification_id.clone(),
+        timestamp_ms: timestamp_ms(item.sent_at),
+        title: item.title.clone(),
+        subtitle: item.subtitle.clone(),
+        body: item.body.clone(),
+        category_id: item.category_id.clone(),
+        thread_id: item.thread_id.clone(),
+        user_metadata: item.user_metadata.clone(),
+    };
+    std::iter::once(sent).chain(
+        item.responses
+            .iter()
+            .map(|response| HistoryEvent::Response {
+                notification_id: item.notification_id.clone(),
+                timestamp_ms: timestamp_ms(response.received_at),
+                action: deeplink::action_id(&response.action).into_owned(),
+                user_input: response.user_input.clone(),
+            }),
+    )
+}
+
+fn timestamp_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn from_timestamp_ms(timestamp_ms: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(timestamp_ms)
+}
+
+/// Manager that records the notifications it sends and their responses in a history
+///
+/// Failures to write the history are logged and do not fail sending.
+#[derive(Debug)]
+pub struct HistoryNotifyManager<M> {
+    inner: M,
+    history: NotificationHistory,
+}
+
+impl<M: NotifyManagerExt> HistoryNotifyManager<M> {
+    pub fn new(inner: M, history: NotificationHistory) -> Self {
+        Self { inner, history }
+    }
+
+    /// Returns the history the manager records to
+    pub fn history(&self) -> &NotificationHistory {
+        &self.history
+    }
+
+    /// Returns the wrapped manager
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<M: NotifyManagerExt> NotifyManagerExt for HistoryNotifyManager<M> {
+    type NotifyHandle = M::NotifyHandle;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        self.inner.get_notification_permission_state().await
+    }
+
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
+        self.inner.get_notification_settings().await
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error> {
+        self.inner
+            .first_time_ask_for_notification_permission()
+            .await
+    }
+
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.inner.request_permission(options).await
+    }
+
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        self.inner.permission_changes().await
+    }
+
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        self.inner.set_badge(count).await
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.inner.get_badge()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.inner.set_should_present(should_present)
+    }
+
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error> {
+        self.inner.set_foreground(foreground)
+    }
+
+    fn register(
+        &self,
+        handler_callback: Box<dyn Fn(NotifyResponse) + Send + Sync + 'static>,
+        categories: Vec<NotifyCategory>,
+    ) -> Result<(), Error> {
+        let history = self.history.clone();
+        self.inner.register(
+            Box::new(move |response| {
+                if let Err(err) = history.record_response(&response) {
+                    log::error!("failed to record notification response: {err}");
+                }
+                handler_callback(response)
+            }),
+            categories,
+        )
+    }
+
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        self.inner.set_categories(categories)
+    }
+
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        self.inner.take_launch_response(args)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), Error> {
+        self.inner.remove_all_delivered_notifications()
+    }
+
+    fn remove_delivered_notifications(&self, ids: Vec<&str>) -> Result<(), Error> {
+        self.inner.remove_delivered_notifications(ids)
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, Error> {
+        self.inner.get_active_notifications().await
+    }
+
+    async fn send(&self, builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
+        let handle = self.inner.send(builder.clone()).await?;
+        if let Err(err) = self.history.record_sent(&handle.get_id(), &builder) {
+            log::error!("failed to record sent notification: {err}");
+        }
+        Ok(handle)
+    }
+}
@@ -1,6 +1,15 @@
+pub mod dedupe;
+pub mod deeplink;
 mod error;
+pub mod history;
+pub mod localization;
+pub mod markup;
 mod notify;
 mod os_impl;
+pub mod quiet_hours;
+pub mod template;
+pub mod throttle;
+pub mod truncation;
 
 pub use error::Error;
 pub use notify::*;
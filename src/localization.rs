@@ -0,0 +1,277 @@
+//! Localization of notification templates and category actions
+//!
+//! A [`Localizer`] translates message ids into the text of a locale. Templates of a
+//! [`TemplateRegistry`] are looked up as `<template>.title`, `<template>.subtitle` and
+//! `<template>.body`, category texts as `<category>.hidden_preview_body_placeholder`,
+//! `<category>.summary_format` and `<category>.<action>.title`,
+//! `<category>.<action>.input_button_title` and `<category>.<action>.input_placeholder`.
+//! Texts without a translation keep the text they were defined with.
+//!
+//! [`FileLocalizer`] reads a Fluent-style subset, one file per locale:
+//!
+//! ```ftl
+//! # de.ftl
+//! build_finished.title = Build {build} abgeschlossen
+//! build_finished.body =
+//!     {project} hat alle Prüfungen bestanden.
+//!     Details im Dashboard.
+//! build.open.title = Öffnen
+//! ```
+//!
+//! Translations use the `{placeholder}` syntax of templates.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    template::TemplateRegistry, Error, NotifyBuilder, NotifyCategory, NotifyCategoryAction,
+    NotifyManagerExt,
+};
+
+/// Translates message ids into the text of a locale
+pub trait Localizer
+where
+    Self: Send + Sync + Debug,
+{
+    /// Returns the text of the message in the locale, `None` if it is not translated
+    fn translate(&self, locale: &str, message_id: &str) -> Option<String>;
+}
+
+/// [`Localizer`] backed by Fluent-style `.ftl` files
+///
+/// Lookups fall back from the locale to its language (`de-AT` to `de`) and then to the
+/// fallback locale.
+#[derive(Debug, Clone, Default)]
+pub struct FileLocalizer {
+    messages: HashMap<String, HashMap<String, String>>,
+    fallback_locale: Option<String>,
+}
+
+impl FileLocalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the locale that is used for messages without a translation in the requested locale
+    pub fn fallback_locale(mut self, locale: &str) -> Self {
+        self.fallback_locale = Some(locale.to_string());
+        self
+    }
+
+    /// Loads every `<locale>.ftl` file of a directory
+    ///
+    /// # Errors
+    /// - `Error::Io` if the directory or a file cannot be read
+    /// - `Error::LocalizationParse` if a file cannot be parsed
+    pub fn load_dir(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("ftl") {
+                continue;
+            }
+            if let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) {
+                let locale = locale.to_string();
+                self.load_file(&locale, &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the messages of a file for the locale
+    pub fn load_file(&mut self, locale: &str, path: impl AsRef<Path>) -> Result<(), Error> {
+        let source = std::fs::read_to_string(path)?;
+        self.add_resource(locale, &source)
+    }
+
+    /// Loads the messages of a Fluent-style document for the locale
+    ///
+    /// Messages are `id = text` lines, where indented lines continue the text of the
+    /// previous message and lines starting with `#` are comments.
+    pub fn add_resource(&mut self, locale: &str, source: &str) -> Result<(), Error> {
+        let mut messages: Vec<(String, String)> = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let parse_error = |reason: &str| {
+                Error::LocalizationParse(format!("{locale}, line {}: {reason}", index + 1))
+            };
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let (_, text) = messages
+                    .last_mut()
+                    .ok_or_else(|| parse_error("continuation line without a message"))?;
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(line.trim());
+                continue;
+            }
+
+            let (id, text) = line
+                .split_once('=')
+                .ok_or_else(|| parse_error("expected `id = text`"))?;
+            let id = id.trim();
+            if id.is_empty()
+                || !id
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            {
+                return Err(parse_error(&format!("invalid message id `{id}`")));
+            }
+            messages.push((id.to_string(), text.trim().to_string()));
+        }
+
+        self.messages
+            .entry(locale.to_string())
+            .or_default()
+            .extend(messages);
+        Ok(())
+    }
+
+    /// Returns the locales that have messages
+    pub fn locales(&self) -> Vec<String> {
+        self.messages.keys().cloned().collect()
+    }
+}
+
+impl Localizer for FileLocalizer {
+    fn translate(&self, locale: &str, message_id: &str) -> Option<String> {
+        let language = locale.split(['-', '_']).next();
+        [Some(locale), language, self.fallback_locale.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|locale| self.messages.get(locale)?.get(message_id))
+            .cloned()
+    }
+}
+
+/// Returns the categories with their texts translated into the locale
+pub fn localize_categories(
+    categories: &[NotifyCategory],
+    localizer: &dyn Localizer,
+    locale: &str,
+) -> Vec<NotifyCategory> {
+    categories
+        .iter()
+        .map(|category| {
+            let translate = |text: &mut String, message_id: String| {
+                if let Some(translation) = localizer.translate(locale, &message_id) {
+                    *text = translation;
+                }
+            };
+
+            let mut category = category.clone();
+            let id = category.identifier.clone();
+            if let Some(placeholder) = &mut category.hidden_preview_body_placeholder {
+                translate(placeholder, format!("{id}.hidden_preview_body_placeholder"));
+            }
+            if let Some(summary_format) = &mut category.summary_format {
+                translate(summary_format, format!("{id}.summary_format"));
+            }
+            for action in &mut category.actions {
+                match action {
+                    NotifyCategoryAction::Action { identifier, title } => {
+                        translate(title, format!("{id}.{identifier}.title"));
+                    }
+                    NotifyCategoryAction::TextInputAction {
+                        identifier,
+                        title,
+                        input_button_title,
+                        input_placeholder,
+                    } => {
+                        translate(title, format!("{id}.{identifier}.title"));
+                        translate(
+                            input_button_title,
+                            format!("{id}.{identifier}.input_button_title"),
+                        );
+                        translate(
+                            input_placeholder,
+                            format!("{id}.{identifier}.input_placeholder"),
+                        );
+                    }
+                }
+            }
+            category
+        })
+        .collect()
+}
+
+/// Current locale with the categories and templates that are translated into it
+///
+/// # Example
+/// ```no_run
+/// # use std::{collections::HashMap, sync::Arc};
+/// # use user_notify_reborn::{localization::*, prelude::*, template::TemplateRegistry};
+/// # fn run(manager: &impl NotifyManagerExt, categories: Vec<NotifyCategory>, templates: TemplateRegistry) -> Result<(), NotifyError> {
+/// let mut localizer = FileLocalizer::new().fallback_locale("en");
+/// localizer.load_dir("locales")?;
+///
+/// let localization = Localization::new(Arc::new(localizer), "en", categories);
+/// manager.register(Box::new(|response| println!("{response:?}")), localization.categories())?;
+///
+/// // the user switched the language of the app
+/// localization.set_locale(manager, "de")?;
+/// let builder = localization.render(&templates, "build_finished", &HashMap::new())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Localization {
+    localizer: Arc<dyn Localizer>,
+    locale: RwLock<String>,
+    categories: Vec<NotifyCategory>,
+}
+
+impl Localization {
+    /// Creates a localization of the categories, which are defined with their untranslated texts
+    pub fn new(
+        localizer: Arc<dyn Localizer>,
+        locale: &str,
+        categories: Vec<NotifyCategory>,
+    ) -> Self {
+        Self {
+            localizer,
+            locale: RwLock::new(locale.to_string()),
+            categories,
+        }
+    }
+
+    /// Returns the current locale
+    pub fn locale(&self) -> String {
+        self.locale
+            .read()
+            .map(|locale| locale.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    /// Returns the categories translated into the current locale, to pass to
+    /// [`NotifyManagerExt::register`]
+    pub fn categories(&self) -> Vec<NotifyCategory> {
+        localize_categories(&self.categories, self.localizer.as_ref(), &self.locale())
+    }
+
+    /// Changes the locale and registers the translated categories with the manager
+    pub fn set_locale<M: NotifyManagerExt>(&self, manager: &M, locale: &str) -> Result<(), Error> {
+        *self
+            .locale
+            .write()
+            .map_err(|_| Error::Other("locale lock poisoned".to_string()))? = locale.to_string();
+        manager.set_categories(self.categories())
+    }
+
+    /// Renders a template in the current locale
+    pub fn render(
+        &self,
+        templates: &TemplateRegistry,
+        template_id: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<NotifyBuilder, Error> {
+        templates.render_localized(template_id, vars, self.localizer.as_ref(), &self.locale())
+    }
+}
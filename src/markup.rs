@@ -0,0 +1,304 @@
+//! Portable body markup
+//!
+//! A Markdown subset that is rendered to freedesktop markup where the notification server
+//! supports it and stripped to plain text elsewhere:
+//!
+//! - `**bold**`
+//! - `*italic*`
+//! - `[label](https://example.com)`, delivered as [`crate::NotifyResponseAction::OpenUrl`]
+//!   when clicked
+//! - `\*` for a literal `*`, likewise for `\`, `[`, `]`, `(` and `)`; `*`, `[` and `]`
+//!   without a counterpart are kept as text as well
+
+use std::fmt::Write;
+
+use quick_xml::escape::{escape, partial_escape};
+use url::Url;
+
+use crate::Error;
+
+/// Part of a body in [`BodyMarkup`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupSpan {
+    Text(String),
+    Bold(Vec<MarkupSpan>),
+    Italic(Vec<MarkupSpan>),
+    Link { label: Vec<MarkupSpan>, url: Url },
+}
+
+/// Parsed body markup
+///
+/// With the `serde` feature, serializes as its source string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct BodyMarkup {
+    source: String,
+    spans: Vec<MarkupSpan>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    Root,
+    Bold,
+    Italic,
+    Link,
+}
+
+impl BodyMarkup {
+    /// Parses the markup
+    ///
+    /// Delimiters without a counterpart are kept as text, e.g. in `Rated 5*`.
+    ///
+    /// # Errors
+    /// - `Error::InvalidMarkup` if emphasis overlaps, links are nested, or the `(url)` of a
+    ///   link is not closed or not an absolute URL
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::InvalidMarkup(format!("{reason} in `{source}`"));
+        let mut stack: Vec<(Frame, Vec<MarkupSpan>)> = vec![(Frame::Root, Vec::new())];
+        let mut chars = source.chars().peekable();
+
+        let mut previous: Option<char> = None;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = chars.next_if(|c| "\\*[]()".contains(*c)).unwrap_or('\\');
+                    push_text(&mut stack, escaped);
+                }
+                '*' => {
+                    let mut run = 1;
+                    while chars.next_if_eq(&'*').is_some() {
+                        run += 1;
+                    }
+                    // like Markdown, only runs right after text close emphasis and only runs
+                    // right before text open it
+                    let can_close = previous.is_some_and(|c| !c.is_whitespace());
+                    let can_open = chars.peek().is_some_and(|c| !c.is_whitespace());
+                    while run > 0 {
+                        let top = stack.last().map(|(frame, _)| *frame);
+                        let close = match top {
+                            Some(Frame::Bold) if can_close && run >= 2 => Some(Frame::Bold),
+                            Some(Frame::Italic) if can_close => Some(Frame::Italic),
+                            _ => None,
+                        };
+
+                        if let Some(frame) = close {
+                            run -= if frame == Frame::Bold { 2 } else { 1 };
+                            let (_, spans) = stack.pop().expect("emphasis frame");
+                            push_span(
+                                &mut stack,
+                                match frame {
+                                    Frame::Bold => MarkupSpan::Bold(spans),
+                                    _ => MarkupSpan::Italic(spans),
+                                },
+                            );
+                        } else if can_open {
+                            let frame = if run >= 2 { Frame::Bold } else { Frame::Italic };
+                            run -= if frame == Frame::Bold { 2 } else { 1 };
+                            if stack.iter().any(|(open, _)| *open == frame) {
+                                return Err(invalid("overlapping emphasis"));
+                            }
+                            stack.push((frame, Vec::new()));
+                        } else {
+                            for _ in 0..run {
+                                push_text(&mut stack, '*');
+                            }
+                            run = 0;
+                        }
+                    }
+                }
+                '[' => {
+                    if stack.iter().any(|(frame, _)| *frame == Frame::Link) {
+                        return Err(invalid("nested link"));
+                    }
+                    stack.push((Frame::Link, Vec::new()));
+                }
+                ']' => {
+                    let top = stack.last().map(|(frame, _)| *frame);
+                    if top == Some(Frame::Link) && chars.next_if_eq(&'(').is_some() {
+                        let mut url = String::new();
+                        loop {
+                            match chars.next() {
+                                Some(')') => break,
+                                Some(c) => url.push(c),
+                                None => return Err(invalid("unclosed link url")),
+                            }
+                        }
+                        let url = Url::parse(url.trim())
+                            .map_err(|err| invalid(&format!("invalid link url `{url}` ({err})")))?;
+                        let (_, label) = stack.pop().expect("link frame");
+                        push_span(&mut stack, MarkupSpan::Link { label, url });
+                    } else {
+                        // a label without `(url)` is text
+                        if top == Some(Frame::Link) {
+                            let (_, label) = stack.pop().expect("link frame");
+                            push_literal(&mut stack, "[", label);
+                        }
+                        push_text(&mut stack, ']');
+                    }
+                }
+                c => push_text(&mut stack, c),
+            }
+            previous = Some(c);
+        }
+
+        // delimiters that were not closed are text
+        while stack.len() > 1 {
+            let (frame, spans) = stack.pop().expect("unclosed frame");
+            let delimiter = match frame {
+                Frame::Bold => "**",
+                Frame::Italic => "*",
+                Frame::Link => "[",
+                Frame::Root => "",
+            };
+            push_literal(&mut stack, delimiter, spans);
+        }
+        let (_, spans) = stack.pop().expect("root frame");
+        Ok(Self {
+            source: source.to_string(),
+            spans,
+        })
+    }
+
+    /// Returns the source of the markup
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the parsed spans
+    pub fn spans(&self) -> &[MarkupSpan] {
+        &self.spans
+    }
+
+    /// Returns the label and URL of every link
+    pub fn links(&self) -> Vec<(String, Url)> {
+        let mut links = Vec::new();
+        collect_links(&mut links, &self.spans);
+        links
+    }
+
+    /// Returns the text without markup, links are replaced by their label
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        write_plain_text(&mut text, &self.spans);
+        text
+    }
+
+    /// Returns the body as [freedesktop markup](https://specifications.freedesktop.org/notification-spec/latest/markup.html)
+    ///
+    /// Links are rendered as `<a href>` if `hyperlinks` is set, else as their label.
+    pub fn to_freedesktop_markup(&self, hyperlinks: bool) -> String {
+        let mut markup = String::new();
+        write_freedesktop_markup(&mut markup, &self.spans, hyperlinks);
+        markup
+    }
+
+    /// Returns the body for a freedesktop notification server with the capabilities
+    ///
+    /// Freedesktop markup if the server has the `body-markup` capability, `<a href>` links
+    /// only with `body-hyperlinks`, else plain text.
+    pub fn render_for_capabilities<S: AsRef<str>>(&self, capabilities: &[S]) -> String {
+        let has = |capability: &str| capabilities.iter().any(|c| c.as_ref() == capability);
+        if has("body-markup") {
+            self.to_freedesktop_markup(has("body-hyperlinks"))
+        } else {
+            self.to_plain_text()
+        }
+    }
+}
+
+impl TryFrom<String> for BodyMarkup {
+    type Error = Error;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<BodyMarkup> for String {
+    fn from(markup: BodyMarkup) -> Self {
+        markup.source
+    }
+}
+
+fn push_span(stack: &mut [(Frame, Vec<MarkupSpan>)], span: MarkupSpan) {
+    if let Some((_, spans)) = stack.last_mut() {
+        spans.push(span);
+    }
+}
+
+/// Pushes the delimiter of a frame that turned out to be text, followed by its spans
+fn push_literal(stack: &mut [(Frame, Vec<MarkupSpan>)], delimiter: &str, spans: Vec<MarkupSpan>) {
+    for c in delimiter.chars() {
+        push_text(stack, c);
+    }
+    for span in spans {
+        match span {
+            MarkupSpan::Text(text) => text.chars().for_each(|c| push_text(stack, c)),
+            span => push_span(stack, span),
+        }
+    }
+}
+
+fn push_text(stack: &mut [(Frame, Vec<MarkupSpan>)], c: char) {
+    let Some((_, spans)) = stack.last_mut() else {
+        return;
+    };
+    match spans.last_mut() {
+        Some(MarkupSpan::Text(text)) => text.push(c),
+        _ => spans.push(MarkupSpan::Text(c.to_string())),
+    }
+}
+
+fn write_plain_text(text: &mut String, spans: &[MarkupSpan]) {
+    for span in spans {
+        match span {
+            MarkupSpan::Text(content) => text.push_str(content),
+            MarkupSpan::Bold(spans)
+            | MarkupSpan::Italic(spans)
+            | MarkupSpan::Link { label: spans, .. } => write_plain_text(text, spans),
+        }
+    }
+}
+
+fn collect_links(links: &mut Vec<(String, Url)>, spans: &[MarkupSpan]) {
+    for span in spans {
+        match span {
+            MarkupSpan::Text(_) => {}
+            MarkupSpan::Bold(spans) | MarkupSpan::Italic(spans) => collect_links(links, spans),
+            MarkupSpan::Link { label, url } => {
+                let mut text = String::new();
+                write_plain_text(&mut text, label);
+                links.push((text, url.clone()));
+            }
+        }
+    }
+}
+
+fn write_freedesktop_markup(markup: &mut String, spans: &[MarkupSpan], hyperlinks: bool) {
+    for span in spans {
+        match span {
+            MarkupSpan::Text(content) => markup.push_str(&partial_escape(content.as_str())),
+            MarkupSpan::Bold(spans) => {
+                markup.push_str("<b>");
+                write_freedesktop_markup(markup, spans, hyperlinks);
+                markup.push_str("</b>");
+            }
+            MarkupSpan::Italic(spans) => {
+                markup.push_str("<i>");
+                write_freedesktop_markup(markup, spans, hyperlinks);
+                markup.push_str("</i>");
+            }
+            MarkupSpan::Link { label, url } if hyperlinks => {
+                let _ = write!(markup, r#"<a href="{}">"#, escape(url.as_str()));
+                write_freedesktop_markup(markup, label, hyperlinks);
+                markup.push_str("</a>");
+            }
+            MarkupSpan::Link { label, .. } => write_freedesktop_markup(markup, label, hyperlinks),
+        }
+    }
+}
@@ -1,18 +1,50 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+    time::{Duration, SystemTime},
+};
 
 use async_trait::async_trait;
+use futures_util::Stream;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::Error;
+use crate::{
+    markup::BodyMarkup,
+    truncation::{self, NotifyPreview, Truncation},
+    Error,
+};
 
+/// Content of a notification to send
+///
+/// With the `serde` feature, serializes as an object of the fields below, where omitted
+/// fields are unset, e.g. `{"title": "Build finished", "category_id": "build"}`.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct NotifyBuilder {
+    pub(crate) id: Option<String>,
     pub(crate) body: Option<String>,
+    pub(crate) body_markup: Option<BodyMarkup>,
     pub(crate) title: Option<String>,
     pub(crate) subtitle: Option<String>,
     pub(crate) thread_id: Option<String>,
     pub(crate) category_id: Option<String>,
-    pub(crate) user_metadata: Option<HashMap<String, String>>,
+    pub(crate) user_metadata: Option<serde_json::Value>,
     pub(crate) sound: Option<String>,
+    pub(crate) urgency: Option<Urgency>,
+    pub(crate) truncation: Option<Truncation>,
+    pub(crate) dedupe_key: Option<String>,
+    pub(crate) presentation: Option<PresentationOptions>,
 }
 
 impl NotifyBuilder {
@@ -20,15 +52,49 @@ impl NotifyBuilder {
         NotifyBuilder::default()
     }
 
+    /// Set the notification id instead of a random one
+    ///
+    /// Sending a notification with the id of a delivered notification replaces it, and it can be
+    /// removed by the id with [`NotifyManagerExt::remove_delivered_notifications`], also after a
+    /// restart of the app. See [`validate_notification_id`] for the allowed ids.
+    ///
+    /// Windows: [ToastNotification/Tag](https://docs.microsoft.com/en-us/uwp/api/windows.ui.notifications.toastnotification.tag)
+    /// macOS: [UNNotificationRequest/identifier](https://developer.apple.com/documentation/usernotifications/unnotificationrequest/identifier)
+    ///
+    /// # Errors
+    /// - `Error::InvalidNotificationId` if the id is not allowed on the current platform
+    pub fn id(mut self, id: &str) -> Result<Self, Error> {
+        validate_notification_id(id, Platform::current())?;
+        self.id = Some(id.to_owned());
+        Ok(self)
+    }
+
     /// Set main content of notification
     ///
     /// Windows: subtitle & content share 4 lines
     /// macOS: subtitle & content share 4 lines, but subtitle only 1 line
     pub fn body(mut self, body: &str) -> Self {
         self.body = Some(body.to_owned());
+        self.body_markup = None;
         self
     }
 
+    /// Set main content of notification with [crate::markup]
+    ///
+    /// Clicked links are delivered as [`NotifyResponseAction::OpenUrl`].
+    ///
+    /// Windows: Markup is stripped to plain text, links become buttons (at most 5 buttons in total),
+    /// the first link without a button opens when the toast is clicked
+    /// macOS: Markup is stripped to plain text, the first link opens when the notification is clicked
+    /// Linux: [Freedesktop markup](https://specifications.freedesktop.org/notification-spec/latest/markup.html)
+    /// if the server has the `body-markup` capability, links are `<a href>` with `body-hyperlinks`
+    pub fn body_markup(mut self, markup: &str) -> Result<Self, Error> {
+        let markup = BodyMarkup::parse(markup)?;
+        self.body = Some(markup.to_plain_text());
+        self.body_markup = Some(markup);
+        Ok(self)
+    }
+
     /// Set primary description of notification
     ///
     /// Windows: 2 lines allowed
@@ -58,6 +124,53 @@ impl NotifyBuilder {
         self
     }
 
+    /// Set notification urgency
+    ///
+    /// Windows: [ToastNotification/Priority](https://docs.microsoft.com/en-us/uwp/api/windows.ui.notifications.toastnotification.priority), `High` for [`Urgency::Critical`]
+    /// macOS: [UNNotificationContent/interruptionLevel](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/interruptionlevel)
+    ///   - `Passive` for [`Urgency::Low`], `Active` for [`Urgency::Normal`], `TimeSensitive` for [`Urgency::Critical`]
+    pub fn urgency(mut self, urgency: Urgency) -> Self {
+        self.urgency = Some(urgency);
+        self
+    }
+
+    /// Set how title, subtitle and body are cut to the text length limits of the platform
+    ///
+    /// By default, texts are sent as they are and the platform cuts them. With
+    /// [`Truncation::enabled`] they are cut to the limits of
+    /// [`truncation::TextLimits::for_platform`] and end with `…`.
+    pub fn truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    /// Set the key under which [crate::dedupe] detects duplicates, instead of the content
+    pub fn dedupe_key(mut self, dedupe_key: &str) -> Self {
+        self.dedupe_key = Some(dedupe_key.to_owned());
+        self
+    }
+
+    /// Set how the notification is presented while the app is in the foreground, instead of
+    /// asking the callback of [`NotifyManagerExt::set_should_present`]
+    pub fn presentation(mut self, presentation: PresentationOptions) -> Self {
+        self.presentation = Some(presentation);
+        self
+    }
+
+    /// Returns the label and URL of every link of the body markup
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    pub(crate) fn links(&self) -> Vec<(String, url::Url)> {
+        self.body_markup
+            .as_ref()
+            .map(BodyMarkup::links)
+            .unwrap_or_default()
+    }
+
+    /// Returns the title, subtitle and body as the platform shows them
+    pub fn preview(&self, platform: Platform) -> NotifyPreview {
+        truncation::preview(self, platform)
+    }
+
     /// Set thread id for grouping related notifications
     ///
     /// Windows: Not supported
@@ -75,9 +188,459 @@ impl NotifyBuilder {
 
     /// Set metadata for a notification
     pub fn set_user_metadata(mut self, user_metadata: HashMap<String, String>) -> Self {
-        self.user_metadata = Some(user_metadata);
+        self.user_metadata = Some(user_metadata.into_iter().collect());
         self
     }
+
+    /// Set structured metadata for a notification
+    ///
+    /// The metadata is stored as JSON and can be read back with [`NotifyResponse::metadata`].
+    ///
+    /// Windows: Stored in the toast's [NotificationData](https://docs.microsoft.com/en-us/uwp/api/windows.ui.notifications.notificationdata)
+    /// macOS: Stored in [UNNotificationContent/userInfo](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/userinfo)
+    pub fn set_metadata<T: Serialize>(mut self, metadata: &T) -> Result<Self, Error> {
+        self.user_metadata =
+            Some(serde_json::to_value(metadata).map_err(Error::FailedToSerializeUserMetadata)?);
+        Ok(self)
+    }
+
+    /// Notification id set with [`NotifyBuilder::id`]
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn get_subtitle(&self) -> Option<&str> {
+        self.subtitle.as_deref()
+    }
+
+    /// Body as plain text, with the markup of [`NotifyBuilder::body_markup`] stripped
+    pub fn get_body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    pub fn get_thread_id(&self) -> Option<&str> {
+        self.thread_id.as_deref()
+    }
+
+    pub fn get_category_id(&self) -> Option<&str> {
+        self.category_id.as_deref()
+    }
+
+    pub fn get_urgency(&self) -> Option<Urgency> {
+        self.urgency
+    }
+
+    /// Metadata of the notification, `None` if none was set
+    pub fn get_user_metadata(&self) -> Option<&serde_json::Value> {
+        self.user_metadata.as_ref()
+    }
+}
+
+/// Platform whose notification center applies, e.g. its layout or id rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    Windows,
+    MacOS,
+    Linux,
+}
+
+impl Platform {
+    /// Returns the platform this crate was compiled for
+    pub fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// Maximum length of a [toast tag](https://docs.microsoft.com/en-us/uwp/api/windows.ui.notifications.toastnotification.tag)
+const WINDOWS_MAX_ID_LEN: usize = 64;
+
+/// Checks that a caller-supplied notification id can be used on the platform
+///
+/// Ids consist of 1 or more ASCII letters, digits, `-`, `_`, `.` and `:`.
+///
+/// Windows: at most 64 characters
+///
+/// # Errors
+/// - `Error::InvalidNotificationId` with the rule the id violates
+pub fn validate_notification_id(id: &str, platform: Platform) -> Result<(), Error> {
+    let invalid = |reason: String| Error::InvalidNotificationId {
+        id: id.to_string(),
+        reason,
+    };
+    if id.is_empty() {
+        return Err(invalid("must not be empty".to_string()));
+    }
+    if let Some(c) = id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || "-_.:".contains(*c)))
+    {
+        return Err(invalid(format!(
+            "character {c:?} is not allowed, use ASCII letters, digits, `-`, `_`, `.` and `:`"
+        )));
+    }
+    if platform == Platform::Windows && id.len() > WINDOWS_MAX_ID_LEN {
+        return Err(invalid(format!(
+            "{} characters exceed the {WINDOWS_MAX_ID_LEN} characters of a Windows toast tag",
+            id.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Urgency of a notification
+///
+/// With the `serde` feature, serializes as `"low"`, `"normal"` or `"critical"`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Urgency {
+    /// Delivered quietly, without interrupting the user
+    Low,
+    #[default]
+    Normal,
+    /// Breaks through focus modes where the platform allows it
+    Critical,
+}
+
+impl FromStr for Urgency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Urgency::Low),
+            "normal" => Ok(Urgency::Normal),
+            "critical" => Ok(Urgency::Critical),
+            _ => Err(Error::Other(format!("unknown urgency `{s}`"))),
+        }
+    }
+}
+
+/// Authorization of the app to post notifications
+///
+/// With the `serde` feature, serializes in snake case, e.g. `"not_determined"`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PermissionState {
+    /// The user was not asked yet
+    #[default]
+    NotDetermined,
+    Denied,
+    Authorized,
+    /// macOS: notifications are delivered quietly to the notification center until the user
+    /// decides
+    Provisional,
+    /// macOS: authorized for a limited time, e.g. for App Clips
+    Ephemeral,
+}
+
+impl PermissionState {
+    /// Returns whether notifications are delivered in this state
+    pub fn is_granted(self) -> bool {
+        matches!(
+            self,
+            PermissionState::Authorized | PermissionState::Provisional | PermissionState::Ephemeral
+        )
+    }
+}
+
+/// Whether a single notification feature is enabled
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum FeatureSetting {
+    /// The platform or the app does not support the feature
+    #[default]
+    NotSupported,
+    Disabled,
+    Enabled,
+}
+
+/// Who disabled the notifications of the app
+///
+/// Windows: `ToastNotifier.Setting`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DisabledBy {
+    /// The user disabled notifications of this app
+    Application,
+    /// The user disabled all notifications
+    User,
+    GroupPolicy,
+    /// The app manifest does not declare toast support
+    Manifest,
+}
+
+/// Permission state with the settings of the single notification features
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotificationSettings {
+    pub state: PermissionState,
+    pub alert: FeatureSetting,
+    pub sound: FeatureSetting,
+    pub badge: FeatureSetting,
+    pub lock_screen: FeatureSetting,
+    /// Windows: set if the state is [`PermissionState::Denied`]
+    pub disabled_by: Option<DisabledBy>,
+}
+
+/// What to ask the user to allow, see [`NotifyManagerExt::request_permission`]
+///
+/// Defaults to alerts, sounds and badges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PermissionOptions {
+    pub alert: bool,
+    pub sound: bool,
+    pub badge: bool,
+    /// macOS: authorize without asking, notifications are delivered quietly to the
+    /// notification center until the user decides
+    pub provisional: bool,
+    /// macOS: play sounds of critical alerts despite focus modes, requires an entitlement
+    pub critical: bool,
+}
+
+impl Default for PermissionOptions {
+    fn default() -> Self {
+        Self {
+            alert: true,
+            sound: true,
+            badge: true,
+            provisional: false,
+            critical: false,
+        }
+    }
+}
+
+impl PermissionOptions {
+    /// Provisional authorization, does not show a permission prompt
+    pub fn provisional() -> Self {
+        Self {
+            provisional: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Stream of permission changes, see [`NotifyManagerExt::permission_changes`]
+pub type PermissionChanges = Pin<Box<dyn Stream<Item = NotificationSettings> + Send>>;
+
+/// Reads the settings with `read` every `interval` and yields them whenever they differ from
+/// the previously read ones, which are `initial` at first
+///
+/// Used by platforms without change events. Failed reads are logged and skipped.
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub(crate) fn poll_permission_changes<F, Fut>(
+    initial: NotificationSettings,
+    interval: Duration,
+    read: F,
+) -> PermissionChanges
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<NotificationSettings, Error>> + Send,
+{
+    Box::pin(futures_util::stream::unfold(
+        (read, initial),
+        move |(read, previous)| async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match read().await {
+                    Ok(settings) if settings != previous => {
+                        return Some((settings, (read, settings)));
+                    }
+                    Ok(_) => {}
+                    Err(err) => log::warn!("failed to read the notification settings: {err}"),
+                }
+            }
+        },
+    ))
+}
+
+/// How a notification is presented while the app is in the foreground
+///
+/// Defaults to presenting it like in the background.
+///
+/// macOS: [UNNotificationPresentationOptions](https://developer.apple.com/documentation/usernotifications/unnotificationpresentationoptions)
+/// Windows: without `banner` the toast goes to the Action Center only, without `banner` and
+/// `list` it is not shown at all. `sound` and `badge` are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PresentationOptions {
+    /// Show the banner
+    pub banner: bool,
+    /// Keep the notification in the notification center
+    pub list: bool,
+    pub sound: bool,
+    pub badge: bool,
+}
+
+impl Default for PresentationOptions {
+    fn default() -> Self {
+        Self {
+            banner: true,
+            list: true,
+            sound: true,
+            badge: true,
+        }
+    }
+}
+
+impl PresentationOptions {
+    /// Do not present the notification
+    pub fn none() -> Self {
+        Self {
+            banner: false,
+            list: false,
+            sound: false,
+            badge: false,
+        }
+    }
+}
+
+/// Notification that is about to be presented while the app is in the foreground, see
+/// [`NotifyManagerExt::set_should_present`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresentedNotification {
+    pub notification_id: String,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: Option<String>,
+    pub category_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub user_metadata: serde_json::Value,
+}
+
+/// Callback of [`NotifyManagerExt::set_should_present`]
+type ShouldPresent = Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>;
+
+/// Foreground state and presentation callback, shared by the platform managers
+#[derive(Default)]
+pub(crate) struct ForegroundPresentation {
+    foreground: AtomicBool,
+    should_present: RwLock<Option<ShouldPresent>>,
+}
+
+impl ForegroundPresentation {
+    pub(crate) fn set_should_present(&self, should_present: ShouldPresent) -> Result<(), Error> {
+        *self
+            .should_present
+            .write()
+            .map_err(|_| Error::Other("presentation lock poisoned".to_string()))? =
+            Some(should_present);
+        Ok(())
+    }
+
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    pub(crate) fn set_foreground(&self, foreground: bool) {
+        self.foreground.store(foreground, Ordering::Relaxed);
+    }
+
+    /// Returns the options of a notification presented while the app is in the foreground,
+    /// `presentation` of the builder overrides the callback
+    pub(crate) fn options(
+        &self,
+        notification: &PresentedNotification,
+        presentation: Option<PresentationOptions>,
+    ) -> PresentationOptions {
+        if let Some(presentation) = presentation {
+            return presentation;
+        }
+        match self.should_present.read() {
+            Ok(should_present) => should_present
+                .as_ref()
+                .map(|should_present| should_present(notification))
+                .unwrap_or_default(),
+            Err(_) => {
+                log::error!("presentation lock poisoned");
+                PresentationOptions::default()
+            }
+        }
+    }
+
+    /// Returns the options of a notification sent by the app, `None` if the app reported that
+    /// it is in the background
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    pub(crate) fn options_for_builder(
+        &self,
+        builder: &NotifyBuilder,
+        notification_id: &str,
+    ) -> Option<PresentationOptions> {
+        if !self.foreground.load(Ordering::Relaxed) {
+            return None;
+        }
+        let notification = PresentedNotification {
+            notification_id: notification_id.to_string(),
+            title: builder.title.clone(),
+            subtitle: builder.subtitle.clone(),
+            body: builder.body.clone(),
+            category_id: builder.category_id.clone(),
+            thread_id: builder.thread_id.clone(),
+            user_metadata: builder.user_metadata.clone().unwrap_or_default(),
+        };
+        Some(self.options(&notification, builder.presentation))
+    }
+}
+
+impl Debug for ForegroundPresentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForegroundPresentation")
+            .field("foreground", &self.foreground)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Content of a delivered notification, kept by the platform handles
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub(crate) struct DeliveredContent {
+    pub(crate) title: Option<String>,
+    pub(crate) subtitle: Option<String>,
+    pub(crate) body: Option<String>,
+    pub(crate) category_id: Option<String>,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) user_metadata: serde_json::Value,
+    pub(crate) delivered_at: Option<SystemTime>,
+}
+
+impl DeliveredContent {
+    /// Content of a notification that is sent now
+    pub(crate) fn from_builder(builder: &NotifyBuilder) -> Self {
+        Self {
+            title: builder.title.clone(),
+            subtitle: builder.subtitle.clone(),
+            body: builder.body.clone(),
+            category_id: builder.category_id.clone(),
+            thread_id: builder.thread_id.clone(),
+            user_metadata: builder.user_metadata.clone().unwrap_or_default(),
+            delivered_at: Some(SystemTime::now()),
+        }
+    }
 }
 
 /// Handle to a sent notification
@@ -90,6 +653,29 @@ where
 
     /// Get the notification ID
     fn get_id(&self) -> String;
+
+    /// Title as delivered, after truncation if enabled
+    fn title(&self) -> Option<&str>;
+
+    /// Subtitle as delivered, after truncation if enabled
+    fn subtitle(&self) -> Option<&str>;
+
+    /// Body as delivered, after truncation if enabled and with markup stripped
+    fn body(&self) -> Option<&str>;
+
+    fn category_id(&self) -> Option<&str>;
+
+    /// Windows: stored with the toast, not used for grouping
+    fn thread_id(&self) -> Option<&str>;
+
+    /// Metadata of the notification, `null` if none was set
+    fn user_metadata(&self) -> &serde_json::Value;
+
+    /// Time the notification was delivered
+    ///
+    /// macOS: `UNNotification.date`
+    /// Windows: stored in the toast's data, `None` for toasts that were not sent by this crate
+    fn delivered_at(&self) -> Option<SystemTime>;
 }
 
 #[async_trait]
@@ -100,11 +686,57 @@ where
     type NotifyHandle: NotifyHandleExt;
 
     /// Get notification permission state
-    async fn get_notification_permission_state(&self) -> Result<bool, crate::Error>;
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, crate::Error>;
+
+    /// Get the permission state and the settings of the single notification features
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error>;
 
     /// Ask for notification permission for the first time
     async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error>;
 
+    /// Ask for notification permission with the given options
+    ///
+    /// Returns the permission state after the user answered, e.g.
+    /// [`PermissionState::Provisional`] for provisional authorization.
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error>;
+
+    /// Stream of the permission settings, yields whenever the user changes them
+    ///
+    /// macOS and Windows poll the settings, Linux listens for the notification server and
+    /// the do-not-disturb state on D-Bus.
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error>;
+
+    /// Set the badge count of the app icon, `None` or `Some(0)` removes the badge
+    ///
+    /// macOS: requires the badge permission, before macOS 14 the dock tile label is set
+    /// instead, which has to happen on the main thread
+    /// Windows: counts above 99 are shown as "99+"
+    /// Linux: shown by docks implementing the Unity launcher API, the bundle id of the
+    /// manager must be the desktop file id
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error>;
+
+    /// Badge count last set with [`NotifyManagerExt::set_badge`]
+    fn get_badge(&self) -> Option<u32>;
+
+    /// Set the callback deciding how notifications are presented while the app is in the
+    /// foreground
+    ///
+    /// [`NotifyBuilder::presentation`] overrides the callback. Without either, notifications
+    /// are presented like in the background.
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error>;
+
+    /// Report whether the app is in the foreground
+    ///
+    /// macOS knows it by itself and ignores this. Windows and Linux apply the presentation
+    /// options only to notifications sent while the app reports that it is in the foreground.
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error>;
+
     /// Register notification handler and categories
     fn register(
         &self,
@@ -112,6 +744,21 @@ where
         categories: Vec<NotifyCategory>,
     ) -> Result<(), Error>;
 
+    /// Replace the registered categories, e.g. with action titles of another locale
+    ///
+    /// Notifications that are sent afterwards use the new categories, the handler stays registered.
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error>;
+
+    /// Take the response of the notification that launched the app
+    ///
+    /// Looks for a notification deeplink among the process arguments (e.g. `std::env::args()`)
+    /// and queues its response, so that the handler receives it once it is registered.
+    ///
+    /// Windows: Requires a notification protocol and [crate::deeplink] secret that outlives the process
+    /// macOS: Clicks that launch the app are queued without calling this, if the manager is
+    /// created on the main thread
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error>;
+
     /// Remove all delivered notifications
     fn remove_all_delivered_notifications(&self) -> Result<(), Error>;
 
@@ -125,35 +772,115 @@ where
     async fn send(&self, builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error>;
 }
 
+/// Interaction of the user with a notification
+///
+/// With the `serde` feature, serializes as an object of the fields below,
+/// e.g. `{"notification_id": "…", "action": "default", "user_input": null, "user_metadata": null}`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NotifyResponse {
     /// ID of the notification that was assigned by the system
     pub notification_id: String,
     pub action: NotifyResponseAction,
     /// The text that the user typed in as response
     pub user_input: Option<String>,
-    pub user_metadata: HashMap<String, String>,
+    /// Metadata of the notification, `null` if none was set
+    pub user_metadata: serde_json::Value,
 }
 
-#[derive(Debug, Clone)]
+impl NotifyResponse {
+    /// Deserialize the metadata of the notification
+    pub fn metadata<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_json::from_value(self.user_metadata.clone()).map_err(Error::FailedToParseUserInfo)
+    }
+}
+
+/// With the `serde` feature, serializes as `"default"`, `"dismiss"`, `"expired"`, `"hidden"`,
+/// `{"open_url": "<url>"}` or `{"other": "<identifier>"}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NotifyResponseAction {
     /// When user clicks on the notification
     Default,
     /// When user closes the notification
     Dismiss,
+    /// When the notification timed out without interaction
+    ///
+    /// Windows: `ToastDismissalReason::TimedOut`
+    /// Linux: `NotificationClosed` reason 1
+    Expired,
+    /// When the notification was removed without the user, e.g. by the app
+    ///
+    /// Windows: `ToastDismissalReason::ApplicationHidden`
+    /// Linux: `NotificationClosed` reasons 3 and 4
+    Hidden,
+    /// When user clicks a link of the [body markup](NotifyBuilder::body_markup)
+    ///
+    /// The URL is not opened, the app decides whether to open it.
+    OpenUrl(url::Url),
     /// The identifier string of the action that the user selected
     Other(String),
 }
 
-#[derive(Debug, Clone)]
+/// With the `serde` feature, serializes as an object of the fields below, where omitted
+/// fields take their default value.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct NotifyCategory {
     /// ID of the category by which it is referenced on notifications
     pub identifier: String,
     /// The actions to display when the system delivers notifications of this type
     pub actions: Vec<NotifyCategoryAction>,
+    /// Report dismissals as [`NotifyResponseAction::Dismiss`]
+    ///
+    /// Windows: Dismissals are always reported
+    /// macOS: [UNNotificationCategoryOptions/customDismissAction](https://developer.apple.com/documentation/usernotifications/unnotificationcategoryoptions/customdismissaction)
+    pub send_dismiss_action: bool,
+    /// Placeholder body shown instead of the content when previews are hidden
+    ///
+    /// Windows: Not supported
+    /// macOS: [UNNotificationCategory/hiddenPreviewsBodyPlaceholder](https://developer.apple.com/documentation/usernotifications/unnotificationcategory/hiddenpreviewsbodyplaceholder)
+    pub hidden_preview_body_placeholder: Option<String>,
+    /// Show the title when previews are hidden
+    ///
+    /// Windows: Not supported
+    /// macOS: [UNNotificationCategoryOptions/hiddenPreviewsShowTitle](https://developer.apple.com/documentation/usernotifications/unnotificationcategoryoptions/hiddenpreviewsshowtitle)
+    pub hidden_previews_show_title: bool,
+    /// Show the subtitle when previews are hidden
+    ///
+    /// Windows: Not supported
+    /// macOS: [UNNotificationCategoryOptions/hiddenPreviewsShowSubtitle](https://developer.apple.com/documentation/usernotifications/unnotificationcategoryoptions/hiddenpreviewsshowsubtitle)
+    pub hidden_previews_show_subtitle: bool,
+    /// Format of the summary shown when notifications of this category are grouped,
+    /// e.g. `"%u new messages"`
+    ///
+    /// Windows: Not supported
+    /// macOS: [UNNotificationCategory/categorySummaryFormat](https://developer.apple.com/documentation/usernotifications/unnotificationcategory/categorysummaryformat)
+    pub summary_format: Option<String>,
+    /// Allow notifications of this category in CarPlay
+    ///
+    /// Windows: Not supported
+    /// macOS: [UNNotificationCategoryOptions/allowInCarPlay](https://developer.apple.com/documentation/usernotifications/unnotificationcategoryoptions/allowincarplay)
+    pub allow_in_car_play: bool,
 }
 
+/// With the `serde` feature, serializes as an object tagged by `type`,
+/// e.g. `{"type": "action", "identifier": "open", "title": "Open"}`
+/// or `{"type": "text_input_action", …}`.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
 pub enum NotifyCategoryAction {
     Action {
         identifier: String,
@@ -166,3 +893,113 @@ pub enum NotifyCategoryAction {
         input_placeholder: String,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_presentation() -> ForegroundPresentation {
+        let presentation = ForegroundPresentation::default();
+        presentation
+            .set_should_present(Box::new(|notification| {
+                if notification.category_id.as_deref() == Some("chat") {
+                    PresentationOptions::none()
+                } else {
+                    PresentationOptions {
+                        banner: false,
+                        ..Default::default()
+                    }
+                }
+            }))
+            .unwrap();
+        presentation
+    }
+
+    #[test]
+    fn test_options_without_callback() {
+        let presentation = ForegroundPresentation::default();
+        assert_eq!(
+            presentation.options(&PresentedNotification::default(), None),
+            PresentationOptions::default()
+        );
+    }
+
+    #[test]
+    fn test_builder_presentation_overrides_callback() {
+        let presentation = quiet_presentation();
+        let notification = PresentedNotification {
+            category_id: Some("chat".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            presentation.options(&notification, Some(PresentationOptions::default())),
+            PresentationOptions::default()
+        );
+        assert_eq!(
+            presentation.options(&notification, None),
+            PresentationOptions::none()
+        );
+    }
+
+    #[test]
+    fn test_options_for_builder_in_background() {
+        let presentation = quiet_presentation();
+        let builder = NotifyBuilder::new()
+            .title("Message")
+            .set_category_id("chat");
+        assert_eq!(presentation.options_for_builder(&builder, "id"), None);
+
+        presentation.set_foreground(true);
+        presentation.set_foreground(false);
+        assert_eq!(presentation.options_for_builder(&builder, "id"), None);
+    }
+
+    #[test]
+    fn test_options_for_builder_in_foreground() {
+        let presentation = ForegroundPresentation::default();
+        presentation
+            .set_should_present(Box::new(|notification| {
+                assert_eq!(notification.notification_id, "id");
+                assert_eq!(notification.title.as_deref(), Some("Message"));
+                assert_eq!(notification.thread_id.as_deref(), Some("room"));
+                PresentationOptions {
+                    banner: false,
+                    ..Default::default()
+                }
+            }))
+            .unwrap();
+        presentation.set_foreground(true);
+
+        let builder = NotifyBuilder::new().title("Message").set_thread_id("room");
+        assert_eq!(
+            presentation.options_for_builder(&builder, "id"),
+            Some(PresentationOptions {
+                banner: false,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            presentation
+                .options_for_builder(&builder.presentation(PresentationOptions::none()), "id"),
+            Some(PresentationOptions::none())
+        );
+    }
+
+    #[test]
+    fn test_options_for_builder_not_shown() {
+        let presentation = quiet_presentation();
+        presentation.set_foreground(true);
+
+        // Windows does not show a toast when both banner and list are off
+        let options = presentation
+            .options_for_builder(&NotifyBuilder::new().set_category_id("chat"), "id")
+            .unwrap();
+        assert!(!options.banner && !options.list);
+        assert_eq!(options, PresentationOptions::none());
+
+        let options = presentation
+            .options_for_builder(&NotifyBuilder::new().set_category_id("mail"), "id")
+            .unwrap();
+        assert!(!options.banner && options.list);
+    }
+}
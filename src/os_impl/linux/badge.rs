@@ -0,0 +1,47 @@
+//! Badge count of the launcher icon
+//!
+//! Docks like Plasma's task manager, Dash to Dock and Plank listen for the `Update` signal of
+//! the Unity launcher API, which is emitted without owning a bus name.
+//!
+//! # References
+//! - [Unity LauncherEntry API](https://wiki.ubuntu.com/Unity/LauncherAPI)
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use zbus::{zvariant::Value, Connection};
+
+use crate::Error;
+
+const LAUNCHER_ENTRY_INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+/// Emits the badge count of the app with the desktop file id `desktop_id`
+///
+/// `None` hides the badge.
+pub(super) async fn update(
+    connection: &Connection,
+    desktop_id: &str,
+    count: Option<u32>,
+) -> Result<(), Error> {
+    let app_uri = format!("application://{desktop_id}.desktop");
+    let mut hasher = DefaultHasher::new();
+    app_uri.hash(&mut hasher);
+    let path = format!("/com/canonical/unity/launcherentry/{}", hasher.finish());
+
+    let properties = HashMap::from([
+        ("count", Value::from(i64::from(count.unwrap_or_default()))),
+        ("count-visible", Value::from(count.is_some())),
+    ]);
+    connection
+        .emit_signal(
+            None::<&str>,
+            path.as_str(),
+            LAUNCHER_ENTRY_INTERFACE,
+            "Update",
+            &(app_uri, properties),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Notifications closed by the notification server
+//!
+//! # References
+//! - [NotificationClosed](https://specifications.freedesktop.org/notification-spec/latest/protocol.html#signal-notification-closed)
+
+use futures_util::{Stream, StreamExt};
+use zbus::Connection;
+
+use super::proxy::NotificationsProxy;
+use crate::{Error, NotifyResponse, NotifyResponseAction};
+
+/// Returns the response action of a `NotificationClosed` reason
+fn action_from_reason(reason: u32) -> NotifyResponseAction {
+    match reason {
+        1 => NotifyResponseAction::Expired,
+        2 => NotifyResponseAction::Dismiss,
+        // 3: closed by `CloseNotification`, 4: undefined or reserved
+        _ => NotifyResponseAction::Hidden,
+    }
+}
+
+/// Returns a stream of the `NotificationClosed` signals as responses
+pub(super) async fn responses(
+    connection: &Connection,
+) -> Result<impl Stream<Item = NotifyResponse> + Send + 'static, Error> {
+    let notifications = NotificationsProxy::new(connection).await?;
+    let closed = notifications.receive_notification_closed().await?;
+    Ok(closed.filter_map(|signal| {
+        let response = signal
+            .args()
+            .inspect_err(|err| log::error!("Linux: invalid NotificationClosed signal: {err}"))
+            .ok()
+            .map(|args| NotifyResponse {
+                notification_id: args.id().to_string(),
+                action: action_from_reason(*args.reason()),
+                user_input: None,
+                user_metadata: serde_json::Value::Null,
+            });
+        std::future::ready(response)
+    }))
+}
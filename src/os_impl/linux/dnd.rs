@@ -0,0 +1,118 @@
+//! Do-not-disturb state of the desktop
+//!
+//! - KDE Plasma: `Inhibited` property of the notification server
+//! - GNOME: `show-banners` setting of `org.gnome.desktop.notifications`, read through the
+//!   settings portal
+//!
+//! # References
+//! - [KDE Inhibited property](https://invent.kde.org/plasma/plasma-workspace/-/blob/master/libnotificationmanager/dbus/org.freedesktop.Notifications.xml)
+//! - [Settings portal](https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html)
+
+use futures_util::{future::Either, Stream, StreamExt};
+use zbus::{proxy, zvariant::Value, Connection};
+
+use super::proxy::NotificationsProxy;
+use crate::Error;
+
+const GNOME_NOTIFICATIONS_NAMESPACE: &str = "org.gnome.desktop.notifications";
+const SHOW_BANNERS_KEY: &str = "show-banners";
+
+#[proxy(
+    interface = "org.freedesktop.portal.Settings",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait PortalSettings {
+    /// Since version 2 of the portal
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<zbus::zvariant::OwnedValue>;
+
+    /// Deprecated, wraps the value in another variant
+    fn read(&self, namespace: &str, key: &str) -> zbus::Result<zbus::zvariant::OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(&self, namespace: &str, key: &str, value: Value<'_>) -> zbus::Result<()>;
+}
+
+/// Returns the bool of a value, that may be wrapped in variants
+fn bool_from_value(value: &Value<'_>) -> Option<bool> {
+    match value {
+        Value::Bool(value) => Some(*value),
+        Value::Value(value) => bool_from_value(value),
+        _ => None,
+    }
+}
+
+async fn gnome_show_banners(settings: &PortalSettingsProxy<'_>) -> Result<bool, Error> {
+    let value = match settings
+        .read_one(GNOME_NOTIFICATIONS_NAMESPACE, SHOW_BANNERS_KEY)
+        .await
+    {
+        Ok(value) => value,
+        Err(_) => {
+            settings
+                .read(GNOME_NOTIFICATIONS_NAMESPACE, SHOW_BANNERS_KEY)
+                .await?
+        }
+    };
+    bool_from_value(&value).ok_or(Error::NotSupported)
+}
+
+/// Returns whether the desktop is in do-not-disturb mode
+///
+/// # Errors
+/// - `Error::NotSupported` if neither the notification server nor the settings portal
+///   provide the state
+pub(super) async fn is_inhibited(connection: &Connection) -> Result<bool, Error> {
+    let notifications = NotificationsProxy::builder(connection)
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()
+        .await?;
+    if let Ok(inhibited) = notifications.inhibited().await {
+        return Ok(inhibited);
+    }
+
+    let settings = PortalSettingsProxy::new(connection).await?;
+    match gnome_show_banners(&settings).await {
+        Ok(show_banners) => Ok(!show_banners),
+        Err(err) => {
+            log::debug!("Linux: do-not-disturb state not available: {err}");
+            Err(Error::NotSupported)
+        }
+    }
+}
+
+/// Returns a stream of the do-not-disturb state, starting with the current state
+pub(super) async fn inhibited_changes(
+    connection: &Connection,
+) -> Result<impl Stream<Item = bool> + Send + 'static, Error> {
+    let initial = is_inhibited(connection).await?;
+
+    let notifications = NotificationsProxy::new(connection).await?;
+    if notifications.inhibited().await.is_ok() {
+        let changes = notifications
+            .receive_inhibited_changed()
+            .await
+            .then(|change| async move { change.get().await.ok() })
+            .filter_map(std::future::ready);
+        return Ok(Either::Left(changes));
+    }
+
+    let settings = PortalSettingsProxy::new(connection).await?;
+    let changes = settings
+        .receive_setting_changed_with_args(&[
+            (0, GNOME_NOTIFICATIONS_NAMESPACE),
+            (1, SHOW_BANNERS_KEY),
+        ])
+        .await?
+        .filter_map(|signal| {
+            let inhibited = signal
+                .args()
+                .ok()
+                .and_then(|args| bool_from_value(args.value()))
+                .map(|show_banners| !show_banners);
+            std::future::ready(inhibited)
+        });
+    Ok(Either::Right(
+        futures_util::stream::once(std::future::ready(initial)).chain(changes),
+    ))
+}
@@ -0,0 +1,287 @@
+//! Linux stub implementation for user-notify-reborn
+//!
+//! This module provides stub implementations for Linux that return
+//! `NotSupported` errors. Sending notifications is not implemented, except that
+//! notifications the presentation callback hides in the foreground are skipped.
+//! The desktop integrations over D-Bus are:
+//! - permission and do-not-disturb state of the desktop
+//! - badge count of the launcher icon
+//! - notifications closed by the notification server
+
+mod badge;
+mod closed;
+mod dnd;
+mod permission;
+mod proxy;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use futures_util::Stream;
+use tokio::sync::OnceCell;
+use zbus::Connection;
+
+use crate::{
+    DeliveredContent, Error, ForegroundPresentation, NotificationSettings, NotifyBuilder,
+    NotifyCategory, NotifyHandleExt, NotifyManagerExt, NotifyResponse, PermissionChanges,
+    PermissionOptions, PermissionState, PresentationOptions, PresentedNotification,
+};
+
+/// A stub handle for notifications on Linux.
+#[derive(Debug)]
+pub struct NotifyHandle {
+    id: String,
+    content: DeliveredContent,
+}
+
+impl NotifyHandle {
+    fn new(id: String, content: DeliveredContent) -> Self {
+        Self { id, content }
+    }
+}
+
+impl NotifyHandleExt for NotifyHandle {
+    fn close(&self) -> Result<(), Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        self.content.delivered_at
+    }
+}
+
+/// Linux stub notification manager.
+#[derive(Debug, Clone)]
+pub struct NotifyManager {
+    /// Session bus connection, opened on first use
+    connection: Arc<OnceCell<Connection>>,
+    /// Desktop file id of the app, without the `.desktop` suffix
+    bundle_id: Option<String>,
+    badge: Arc<Mutex<Option<u32>>>,
+    /// Foreground state and presentation callback
+    presentation: Arc<ForegroundPresentation>,
+}
+
+impl NotifyManager {
+    /// Creates a new notification manager instance (stub).
+    #[allow(clippy::new_without_default)]
+    pub fn new_() -> Self {
+        Self {
+            connection: Arc::new(OnceCell::new()),
+            bundle_id: None,
+            badge: Arc::new(Mutex::new(None)),
+            presentation: Arc::new(ForegroundPresentation::default()),
+        }
+    }
+
+    /// Attempts to create a new notification manager (stub).
+    ///
+    /// `bundle_id` is the desktop file id of the app, e.g. `org.example.App` for
+    /// `org.example.App.desktop`.
+    pub fn try_new(bundle_id: &str, _category_identifier: Option<&str>) -> Result<Self, Error> {
+        Ok(Self {
+            bundle_id: Some(bundle_id.to_string()).filter(|bundle_id| !bundle_id.is_empty()),
+            ..Self::new_()
+        })
+    }
+
+    /// Uses `connection` instead of connecting to the session bus
+    pub fn with_connection(self, connection: Connection) -> Self {
+        Self {
+            connection: Arc::new(OnceCell::new_with(Some(connection))),
+            ..self
+        }
+    }
+
+    /// Sets the secret used to verify notification deeplinks (stub).
+    pub fn with_deeplink_secret(self, _secret: &[u8]) -> Self {
+        self
+    }
+
+    /// Returns whether the desktop is in do-not-disturb mode
+    ///
+    /// Reads the `Inhibited` property of the notification server on KDE Plasma and the
+    /// `show-banners` setting on GNOME.
+    ///
+    /// # Errors
+    /// - `Error::DBus` if the session bus is not available
+    /// - `Error::NotSupported` if the desktop does not expose the state
+    pub async fn is_inhibited(&self) -> Result<bool, Error> {
+        dnd::is_inhibited(self.connection().await?).await
+    }
+
+    /// Returns a stream of the do-not-disturb state, starting with the current state
+    ///
+    /// # Errors
+    /// Same as [`NotifyManager::is_inhibited`]
+    pub async fn inhibited_changes(
+        &self,
+    ) -> Result<impl Stream<Item = bool> + Send + 'static, Error> {
+        dnd::inhibited_changes(self.connection().await?).await
+    }
+
+    /// Returns a stream of the notifications that the notification server closed, e.g. of
+    /// notifications shown with another library
+    ///
+    /// The response id is the id the server assigned to the notification, the action is
+    /// `Expired`, `Dismiss` or `Hidden` for the `NotificationClosed` reasons 1, 2 and 3 or 4.
+    ///
+    /// # Errors
+    /// - `Error::DBus` if the session bus is not available
+    pub async fn closed_notifications(
+        &self,
+    ) -> Result<impl Stream<Item = NotifyResponse> + Send + 'static, Error> {
+        closed::responses(self.connection().await?).await
+    }
+
+    async fn connection(&self) -> Result<&Connection, Error> {
+        Ok(self.connection.get_or_try_init(Connection::session).await?)
+    }
+}
+
+#[async_trait]
+impl NotifyManagerExt for NotifyManager {
+    type NotifyHandle = NotifyHandle;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        Ok(self.get_notification_settings().await?.state)
+    }
+
+    /// Authorized while a notification server is running, alerts are disabled in
+    /// do-not-disturb mode
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
+        permission::settings(self.connection().await?).await
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error> {
+        Err(Error::NotSupported)
+    }
+
+    /// Linux has no permission prompt, returns the current permission state
+    async fn request_permission(
+        &self,
+        _options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.get_notification_permission_state().await
+    }
+
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        permission::changes(self.connection().await?.clone()).await
+    }
+
+    /// Emits the Unity `LauncherEntry.Update` signal for the desktop file of the bundle id
+    ///
+    /// # Errors
+    /// - `Error::NoBundleId` if the manager was not created with a bundle id
+    /// - `Error::DBus` if the session bus is not available
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        let bundle_id = self.bundle_id.as_deref().ok_or(Error::NoBundleId)?;
+        let count = count.filter(|count| *count > 0);
+        badge::update(self.connection().await?, bundle_id, count).await?;
+        *self
+            .badge
+            .lock()
+            .map_err(|_| Error::Other("badge lock poisoned".to_string()))? = count;
+        Ok(())
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.badge.lock().map(|badge| *badge).unwrap_or_default()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.presentation.set_should_present(should_present)
+    }
+
+    /// Notifications sent in the foreground use the presentation options
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error> {
+        self.presentation.set_foreground(foreground);
+        Ok(())
+    }
+
+    fn register(
+        &self,
+        _handler_callback: Box<dyn Fn(crate::NotifyResponse) + Send + Sync + 'static>,
+        _categories: Vec<NotifyCategory>,
+    ) -> Result<(), Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn set_categories(&self, _categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn take_launch_response(&self, _args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn remove_delivered_notifications(&self, _ids: Vec<&str>) -> Result<(), Error> {
+        Err(Error::NotSupported)
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, Error> {
+        Err(Error::NotSupported)
+    }
+
+    /// Only skips notifications that are not presented in the foreground, sending
+    /// others returns `Error::NotSupported`
+    async fn send(&self, builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
+        let notification_id = builder
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        if let Some(PresentationOptions {
+            banner: false,
+            list: false,
+            ..
+        }) = self
+            .presentation
+            .options_for_builder(&builder, &notification_id)
+        {
+            log::debug!("Linux: not presenting notification {notification_id} in the foreground");
+            return Ok(NotifyHandle::new(
+                notification_id,
+                DeliveredContent::from_builder(&builder),
+            ));
+        }
+        Err(Error::NotSupported)
+    }
+}
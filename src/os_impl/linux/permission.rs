@@ -0,0 +1,76 @@
+//! Permission state of the desktop notification server
+//!
+//! Notifications are authorized while a notification server owns `org.freedesktop.Notifications`,
+//! alerts are disabled while the desktop is in do-not-disturb mode.
+
+use futures_util::{future::Either, stream, StreamExt};
+use zbus::{fdo::DBusProxy, names::BusName, Connection};
+
+use super::dnd;
+use crate::{Error, FeatureSetting, NotificationSettings, PermissionChanges, PermissionState};
+
+const NOTIFICATIONS_SERVICE: &str = "org.freedesktop.Notifications";
+
+/// Reads the permission settings
+///
+/// # Errors
+/// - `Error::DBus` if the bus can not be queried
+pub(super) async fn settings(connection: &Connection) -> Result<NotificationSettings, Error> {
+    let dbus = DBusProxy::new(connection).await?;
+    let name = BusName::from_static_str(NOTIFICATIONS_SERVICE).map_err(zbus::Error::from)?;
+    let has_server = dbus.name_has_owner(name).await.map_err(zbus::Error::from)?;
+    if !has_server {
+        return Ok(NotificationSettings {
+            state: PermissionState::Denied,
+            alert: FeatureSetting::Disabled,
+            ..Default::default()
+        });
+    }
+
+    let alert = match dnd::is_inhibited(connection).await {
+        Ok(true) => FeatureSetting::Disabled,
+        Ok(false) | Err(Error::NotSupported) => FeatureSetting::Enabled,
+        Err(err) => return Err(err),
+    };
+    Ok(NotificationSettings {
+        state: PermissionState::Authorized,
+        alert,
+        ..Default::default()
+    })
+}
+
+/// Re-reads the settings whenever the notification server appears or disappears, or the
+/// do-not-disturb state changes
+pub(super) async fn changes(connection: Connection) -> Result<PermissionChanges, Error> {
+    let initial = settings(&connection).await?;
+
+    let dbus = DBusProxy::new(&connection).await?;
+    let server_changes = dbus
+        .receive_name_owner_changed_with_args(&[(0, NOTIFICATIONS_SERVICE)])
+        .await?
+        .map(|_| ());
+    let inhibited_changes = match dnd::inhibited_changes(&connection).await {
+        Ok(changes) => Either::Left(changes.map(|_| ())),
+        Err(Error::NotSupported) => Either::Right(stream::empty()),
+        Err(err) => return Err(err),
+    };
+    let events = Box::pin(stream::select(server_changes, inhibited_changes));
+
+    Ok(Box::pin(stream::unfold(
+        (events, connection, initial),
+        |(mut events, connection, previous)| async move {
+            loop {
+                events.next().await?;
+                match settings(&connection).await {
+                    Ok(settings) if settings != previous => {
+                        return Some((settings, (events, connection, settings)));
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        log::warn!("Linux: failed to read the notification settings: {err}")
+                    }
+                }
+            }
+        },
+    )))
+}
@@ -0,0 +1,20 @@
+//! Proxy of the desktop notification server
+//!
+//! # References
+//! - [Desktop Notifications Specification](https://specifications.freedesktop.org/notification-spec/latest/protocol.html)
+
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+pub(super) trait Notifications {
+    /// KDE extension, not implemented by other notification servers
+    #[zbus(property)]
+    fn inhibited(&self) -> zbus::Result<bool>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
@@ -1,42 +1,39 @@
-use std::{collections::HashMap, ops::Deref};
+use std::ops::Deref;
 
-use super::{NotifyHandle, NotifyManager};
+use super::{
+    presentation_to_user_info, NotifyHandle, NotifyManager, DEFAULT_URL_KEY, PRESENTATION_KEY,
+    USER_METADATA_JSON_KEY,
+};
 use objc2::{rc::Retained, runtime::AnyObject};
 use objc2_foundation::{NSDictionary, NSString};
 use objc2_user_notifications::{
-    UNMutableNotificationContent, UNNotificationRequest, UNNotificationSound,
+    UNMutableNotificationContent, UNNotificationInterruptionLevel, UNNotificationRequest,
+    UNNotificationSound,
 };
 use uuid::Uuid;
 
-use crate::{Error, NotifyBuilder};
+use crate::{validate_notification_id, DeliveredContent, Error, NotifyBuilder, Platform, Urgency};
 
 pub(super) fn build_and_send(
     builder: NotifyBuilder,
     manager: &NotifyManager,
     tx: tokio::sync::oneshot::Sender<Result<(), Error>>,
 ) -> Result<NotifyHandle, Error> {
-    let (request, id, user_info) = build(builder, manager)?;
+    let content = DeliveredContent::from_builder(&builder);
+    let (request, id) = build(builder, manager)?;
     manager.add_notification(&request, move |result| {
         if let Err(err) = tx.send(result) {
             log::error!("add_notification tx.send error {err:?}");
         }
     });
-    Ok(NotifyHandle::new(id, user_info))
+    Ok(NotifyHandle::new(id, content))
 }
 
-#[allow(clippy::type_complexity)]
 fn build(
     builder: NotifyBuilder,
     manager: &NotifyManager,
-) -> Result<
-    (
-        Retained<UNNotificationRequest>,
-        String,
-        HashMap<String, String>,
-    ),
-    Error,
-> {
-    let mut user_info = HashMap::new();
+) -> Result<(Retained<UNNotificationRequest>, String), Error> {
+    let default_url = builder.links().into_iter().next().map(|(_, url)| url);
 
     let notification: Retained<UNMutableNotificationContent> = unsafe {
         let notification = UNMutableNotificationContent::new();
@@ -64,6 +61,14 @@ fn build(
             notification.setSound(Some(&UNNotificationSound::defaultSound()));
         }
 
+        if let Some(urgency) = builder.urgency {
+            notification.setInterruptionLevel(match urgency {
+                Urgency::Low => UNNotificationInterruptionLevel::Passive,
+                Urgency::Normal => UNNotificationInterruptionLevel::Active,
+                Urgency::Critical => UNNotificationInterruptionLevel::TimeSensitive,
+            });
+        }
+
         if let Some(thread_id) = builder.thread_id {
             notification.setThreadIdentifier(&NSString::from_str(&thread_id));
         }
@@ -71,29 +76,29 @@ fn build(
             notification.setCategoryIdentifier(&NSString::from_str(&category_id));
         }
 
+        let mut user_info_entries = Vec::new();
+        if let Some(url) = default_url {
+            user_info_entries.push((DEFAULT_URL_KEY, url.to_string()));
+        }
         if let Some(payload) = builder.user_metadata {
-            let mut user_info_keys = Vec::with_capacity(payload.len());
-            let mut user_info_values = Vec::with_capacity(payload.len());
-            for (key, value) in payload.iter() {
-                user_info_keys.push(NSString::from_str(key));
-                user_info_values.push(NSString::from_str(value));
-            }
-            let string_dictionary = NSDictionary::from_slices(
-                user_info_keys
-                    .iter()
-                    .map(|r| r.deref())
-                    .collect::<Vec<&NSString>>()
-                    .as_slice(),
-                user_info_values
-                    .iter()
-                    .map(|r| r.deref())
-                    .collect::<Vec<&NSString>>()
-                    .as_slice(),
-            );
+            // Stored as JSON, as userInfo only round-trips property list types
+            user_info_entries.push((USER_METADATA_JSON_KEY, payload.to_string()));
+        }
+        if let Some(presentation) = builder.presentation {
+            user_info_entries.push((PRESENTATION_KEY, presentation_to_user_info(presentation)));
+        }
+
+        if !user_info_entries.is_empty() {
+            let (keys, values): (Vec<_>, Vec<_>) = user_info_entries
+                .into_iter()
+                .map(|(key, value)| (NSString::from_str(key), NSString::from_str(&value)))
+                .unzip();
+            let keys: Vec<&NSString> = keys.iter().map(|key| key.deref()).collect();
+            let values: Vec<&NSString> = values.iter().map(|value| value.deref()).collect();
+            let string_dictionary = NSDictionary::from_slices(&keys, &values);
             let anyobject_dictionary =
                 Retained::cast_unchecked::<NSDictionary<AnyObject, AnyObject>>(string_dictionary);
             notification.setUserInfo(anyobject_dictionary.deref());
-            user_info = payload;
         }
 
         notification
@@ -107,7 +112,13 @@ fn build(
             .map(|s| NSString::from_str(s))
             .ok_or(Error::NoBundleId)?;
 
-        let id = format!("{}.{}", Uuid::new_v4(), bundle_id);
+        let id = match builder.id {
+            Some(id) => {
+                validate_notification_id(&id, Platform::MacOS)?;
+                id
+            }
+            None => format!("{}.{}", Uuid::new_v4(), bundle_id),
+        };
 
         let r = UNNotificationRequest::requestWithIdentifier_content_trigger(
             &NSString::from_str(&id),
@@ -115,6 +126,6 @@ fn build(
             None,
         );
 
-        Ok((r, id, user_info))
+        Ok((r, id))
     }
 }
@@ -1,4 +1,12 @@
-use crate::{macos::user_info_dictionary_to_hashmap, NotifyResponse, NotifyResponseAction};
+use std::sync::Arc;
+
+use crate::{
+    macos::{
+        user_info_dictionary_to_default_url, user_info_dictionary_to_metadata,
+        user_info_dictionary_to_presentation,
+    },
+    ForegroundPresentation, NotifyResponse, NotifyResponseAction, PresentedNotification,
+};
 use objc2::{define_class, msg_send, rc::Retained, DefinedClass, MainThreadMarker, MainThreadOnly};
 use objc2_foundation::{NSObject, NSObjectProtocol};
 use objc2_user_notifications::{
@@ -11,6 +19,7 @@ use tokio::sync::mpsc::Sender;
 #[derive(Clone)]
 pub struct Ivars {
     pub sender: Sender<NotifyResponse>,
+    pub presentation: Arc<ForegroundPresentation>,
 }
 
 define_class!(
@@ -29,14 +38,33 @@ define_class!(
         fn will_present_notification(
             &self,
             _center: &UNUserNotificationCenter,
-            _notification: &UNNotification,
+            notification: &UNNotification,
             completion_handler: &block2::Block<dyn Fn(UNNotificationPresentationOptions)>,
         ) {
             log::debug!("macOS: Will present notification");
-            let presentation_options = UNNotificationPresentationOptions::empty()
-                .union(UNNotificationPresentationOptions::Badge)
-                .union(UNNotificationPresentationOptions::Banner)
-                .union(UNNotificationPresentationOptions::Sound);
+            let request = notification.request();
+            let content = request.content();
+            let user_info = content.userInfo();
+            let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+            let presented = PresentedNotification {
+                notification_id: request.identifier().to_string(),
+                title: non_empty(content.title().to_string()),
+                subtitle: non_empty(content.subtitle().to_string()),
+                body: non_empty(content.body().to_string()),
+                category_id: non_empty(content.categoryIdentifier().to_string()),
+                thread_id: non_empty(content.threadIdentifier().to_string()),
+                user_metadata: user_info_dictionary_to_metadata(user_info.clone()),
+            };
+            let presentation = self
+                .ivars()
+                .presentation
+                .options(&presented, user_info_dictionary_to_presentation(&user_info));
+
+            let mut presentation_options = UNNotificationPresentationOptions::empty();
+            presentation_options.set(UNNotificationPresentationOptions::Banner, presentation.banner);
+            presentation_options.set(UNNotificationPresentationOptions::List, presentation.list);
+            presentation_options.set(UNNotificationPresentationOptions::Sound, presentation.sound);
+            presentation_options.set(UNNotificationPresentationOptions::Badge, presentation.badge);
             completion_handler.call((presentation_options,));
         }
 
@@ -50,9 +78,18 @@ define_class!(
             log::debug!("macOS: Did receive notification response");
 
             unsafe {
+                let notification = response.notification();
+                let request = notification.request();
+                let notification_id = request.identifier().to_string();
+                let user_info = request.content().userInfo();
+
                 let action_id = response.actionIdentifier();
                 let action: NotifyResponseAction = match &*action_id {
-                    a if a == UNNotificationDefaultActionIdentifier => NotifyResponseAction::Default,
+                    a if a == UNNotificationDefaultActionIdentifier => {
+                        user_info_dictionary_to_default_url(&user_info)
+                            .map(NotifyResponseAction::OpenUrl)
+                            .unwrap_or(NotifyResponseAction::Default)
+                    }
                     a if a == UNNotificationDismissActionIdentifier => NotifyResponseAction::Dismiss,
                     _ => NotifyResponseAction::Other(action_id.to_string()),
                 };
@@ -61,10 +98,7 @@ define_class!(
                     .downcast_ref::<UNTextInputNotificationResponse>()
                     .map(|text_response| text_response.userText().to_string());
 
-                let notification = response.notification();
-                let request = notification.request();
-                let notification_id = request.identifier().to_string();
-                let user_metadata = user_info_dictionary_to_hashmap(request.content().userInfo());
+                let user_metadata = user_info_dictionary_to_metadata(user_info);
 
                 let event = NotifyResponse {
                     notification_id,
@@ -84,8 +118,15 @@ define_class!(
 );
 
 impl NotificationDelegate {
-    pub fn new(mtm: MainThreadMarker, tx: Sender<NotifyResponse>) -> Retained<Self> {
-        let this = Self::alloc(mtm).set_ivars(Ivars { sender: tx });
+    pub fn new(
+        mtm: MainThreadMarker,
+        tx: Sender<NotifyResponse>,
+        presentation: Arc<ForegroundPresentation>,
+    ) -> Retained<Self> {
+        let this = Self::alloc(mtm).set_ivars(Ivars {
+            sender: tx,
+            presentation,
+        });
         unsafe { msg_send![super(this), init] }
     }
 }
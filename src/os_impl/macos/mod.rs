@@ -2,22 +2,28 @@ mod builder;
 mod delegate;
 
 use crate::{
-    Error, NotifyBuilder, NotifyCategory, NotifyHandleExt, NotifyManagerExt, NotifyResponse,
+    deeplink, poll_permission_changes, truncation, DeliveredContent, Error, FeatureSetting,
+    ForegroundPresentation, NotificationSettings, NotifyBuilder, NotifyCategory, NotifyHandleExt,
+    NotifyManagerExt, NotifyResponse, PermissionChanges, PermissionOptions, PermissionState,
+    Platform,
 };
 use async_trait::async_trait;
 use builder::build_and_send;
 use delegate::NotificationDelegate;
 use objc2::{
+    msg_send,
     rc::Retained,
-    runtime::{AnyObject, Bool, ProtocolObject},
-    MainThreadMarker, Message,
+    runtime::{AnyClass, AnyObject, Bool, ProtocolObject},
+    sel, ClassType, MainThreadMarker, Message,
+};
+use objc2_foundation::{
+    NSArray, NSBundle, NSDate, NSDictionary, NSError, NSInteger, NSSet, NSString,
 };
-use objc2_foundation::{NSArray, NSBundle, NSDictionary, NSError, NSSet, NSString};
 use objc2_user_notifications::{
     UNAuthorizationOptions, UNAuthorizationStatus, UNNotification, UNNotificationAction,
     UNNotificationActionOptions, UNNotificationCategory, UNNotificationCategoryOptions,
-    UNNotificationRequest, UNNotificationSettings, UNTextInputNotificationAction,
-    UNUserNotificationCenter, UNUserNotificationCenterDelegate,
+    UNNotificationRequest, UNNotificationSetting, UNNotificationSettings,
+    UNTextInputNotificationAction, UNUserNotificationCenter, UNUserNotificationCenterDelegate,
 };
 use send_wrapper::SendWrapper;
 use std::{
@@ -25,9 +31,11 @@ use std::{
     collections::HashMap,
     ops::Deref,
     ptr::NonNull,
-    sync::Arc,
+    sync::{Arc, Mutex},
     thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use tokio::sync::mpsc::{Receiver, Sender};
 
 // ============================================================================
 // Constants and Type Aliases
@@ -36,6 +44,18 @@ use std::{
 /// Maximum number of notifications that can be queued in the response channel
 const NOTIFICATION_RESPONSE_CHANNEL_SIZE: usize = 10;
 
+/// How often `permission_changes` reads the notification settings
+const PERMISSION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Key of the userInfo entry holding the JSON encoded user metadata
+const USER_METADATA_JSON_KEY: &str = "UserNotifyRebornMetadataJson";
+
+/// Key of the userInfo entry holding the URL that opens when the notification is clicked
+const DEFAULT_URL_KEY: &str = "UserNotifyRebornDefaultUrl";
+
+/// Key of the userInfo entry holding the presentation options set on the builder
+const PRESENTATION_KEY: &str = "UserNotifyRebornPresentation";
+
 /// Type alias for the delegate reference stored in the manager
 type DelegateReference =
     SendWrapper<OnceCell<Retained<ProtocolObject<dyn UNUserNotificationCenterDelegate>>>>;
@@ -56,13 +76,13 @@ type ListenerHandle = SendWrapper<OnceCell<thread::JoinHandle<()>>>;
 /// - [UNUserNotificationCenter](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter)
 /// - [UNNotification](https://developer.apple.com/documentation/usernotifications/unnotification)
 #[derive(Debug)]
-#[allow(dead_code)]
 pub struct NotifyHandle {
     /// Unique identifier for the notification
     ///
     /// This corresponds to the `identifier` property of `UNNotificationRequest`
     id: String,
-    user_info: HashMap<String, String>,
+    /// Content of the `UNNotificationRequest` and the `date` of the `UNNotification`
+    content: DeliveredContent,
 }
 
 impl NotifyHandle {
@@ -70,15 +90,12 @@ impl NotifyHandle {
     ///
     /// # Arguments
     /// * `id` - Unique identifier for the notification
-    /// * `user_data` - User-defined metadata
+    /// * `content` - Content and delivery date of the notification
     ///
     /// # Returns
     /// A new `NotifyHandle` instance
-    pub(super) fn new(id: String, user_data: HashMap<String, String>) -> Self {
-        Self {
-            id,
-            user_info: user_data,
-        }
+    pub(super) fn new(id: String, content: DeliveredContent) -> Self {
+        Self { id, content }
     }
 
     /// Validates that we're running on the main thread
@@ -151,6 +168,36 @@ impl NotifyHandleExt for NotifyHandle {
     fn get_id(&self) -> String {
         self.id.clone()
     }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    /// Returns the `date` of the `UNNotification`, or the time it was sent for handles
+    /// returned by `send`
+    fn delivered_at(&self) -> Option<SystemTime> {
+        self.content.delivered_at
+    }
 }
 
 // ============================================================================
@@ -175,11 +222,26 @@ pub struct NotifyManagerInner {
     /// to the user-provided callback function.
     listener_loop: ListenerHandle,
 
+    /// Sending side of the response channel, shared with the notification delegate
+    response_sender: Sender<NotifyResponse>,
+
+    /// Receiving side of the response channel
+    ///
+    /// Responses are buffered here until `register` hands the receiver to the
+    /// listener loop, so clicks that launched the app are not lost.
+    response_receiver: Mutex<Option<Receiver<NotifyResponse>>>,
+
     /// The application's bundle identifier
     ///
     /// Required for all notification operations on macOS.
     /// Derived from `NSBundle.mainBundle.bundleIdentifier`.
     pub(crate) bundle_id: Option<String>,
+
+    /// Badge count last set with `set_badge`
+    badge: Mutex<Option<u32>>,
+
+    /// Presentation callback, shared with the notification delegate
+    presentation: Arc<ForegroundPresentation>,
 }
 
 /// macOS implementation of the notification manager
@@ -205,6 +267,12 @@ pub struct NotifyManagerInner {
 pub struct NotifyManager {
     /// Shared internal state
     pub(super) inner: Arc<NotifyManagerInner>,
+
+    /// Scheme of the notification deeplinks passed to `take_launch_response`
+    notification_protocol: Option<String>,
+
+    /// Secret used to verify notification deeplinks
+    deeplink_secret: Arc<[u8]>,
 }
 
 impl NotifyManager {
@@ -218,22 +286,87 @@ impl NotifyManager {
     /// If no bundle identifier is available, notification operations will fail.
     #[allow(clippy::new_without_default)]
     pub fn new_() -> Self {
+        let (response_sender, response_receiver) =
+            tokio::sync::mpsc::channel::<NotifyResponse>(NOTIFICATION_RESPONSE_CHANNEL_SIZE);
+
         Self {
             inner: Arc::new(NotifyManagerInner {
                 delegate_reference: SendWrapper::new(OnceCell::new()),
                 listener_loop: SendWrapper::new(OnceCell::new()),
+                response_sender,
+                response_receiver: Mutex::new(Some(response_receiver)),
                 bundle_id: Self::get_bundle_identifier(),
+                badge: Mutex::new(None),
+                presentation: Arc::new(ForegroundPresentation::default()),
             }),
+            notification_protocol: None,
+            deeplink_secret: deeplink::generate_secret(),
         }
     }
 
-    pub fn try_new(_bundle_id: &str, _category_identifier: Option<&str>) -> Result<Self, Error> {
+    /// Creates a new notification manager
+    ///
+    /// When called from the main thread, the notification delegate is installed right away,
+    /// so that the response to a notification that launched the app is delivered to the
+    /// handler once it is registered.
+    ///
+    /// # References
+    /// - [UNUserNotificationCenterDelegate](https://developer.apple.com/documentation/usernotifications/unusernotificationcenterdelegate)
+    pub fn try_new(_bundle_id: &str, notification_protocol: Option<&str>) -> Result<Self, Error> {
         use objc2_foundation::NSBundle;
         if unsafe { NSBundle::mainBundle().bundleIdentifier().is_none() } {
             return Err(Error::NoBundleId);
         }
 
-        Ok(Self::new_())
+        let manager = Self {
+            notification_protocol: notification_protocol.map(|s| s.to_string()),
+            ..Self::new_()
+        };
+
+        if let Some(mtm) = MainThreadMarker::new() {
+            manager.install_delegate(mtm)?;
+        }
+
+        Ok(manager)
+    }
+
+    /// Sets the secret used to verify notification deeplinks in `take_launch_response`
+    ///
+    /// # References
+    /// - [crate::deeplink]
+    pub fn with_deeplink_secret(mut self, secret: &[u8]) -> Self {
+        self.deeplink_secret = Arc::from(secret);
+        self
+    }
+
+    /// Installs the notification delegate that forwards responses to the response channel
+    ///
+    /// # Errors
+    /// - `Error::MultipleRegisterCalls` if the delegate is already installed
+    ///
+    /// # References
+    /// - [UNUserNotificationCenter.delegate](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/delegate)
+    fn install_delegate(&self, mtm: MainThreadMarker) -> Result<(), Error> {
+        let notification_delegate = NotificationDelegate::new(
+            mtm,
+            self.inner.response_sender.clone(),
+            self.inner.presentation.clone(),
+        );
+
+        unsafe {
+            let proto: Retained<ProtocolObject<dyn UNUserNotificationCenterDelegate>> =
+                ProtocolObject::from_retained(notification_delegate);
+
+            UNUserNotificationCenter::currentNotificationCenter().setDelegate(Some(&*proto));
+
+            // Store delegate reference to prevent deallocation
+            self.inner
+                .delegate_reference
+                .set(proto)
+                .map_err(|_| Error::MultipleRegisterCalls)?;
+        }
+
+        Ok(())
     }
 
     /// Retrieves the application's bundle identifier
@@ -259,6 +392,38 @@ impl NotifyManager {
         self.inner.bundle_id.as_deref().ok_or(Error::NoBundleId)
     }
 
+    /// Sets the badge label of the dock tile, used before macOS 14
+    ///
+    /// # Errors
+    /// - `Error::NotMainThread` if not called from the main thread
+    /// - `Error::Other` if AppKit is not loaded
+    ///
+    /// # References
+    /// - [NSDockTile.badgeLabel](https://developer.apple.com/documentation/appkit/nsdocktile/badgelabel)
+    fn set_dock_tile_badge(count: Option<u32>) -> Result<(), Error> {
+        MainThreadMarker::new().ok_or(Error::NotMainThread)?;
+        let application = AnyClass::get(c"NSApplication")
+            .ok_or_else(|| Error::Other("AppKit is not loaded".to_string()))?;
+        let label = count.map(|count| NSString::from_str(&count.to_string()));
+
+        unsafe {
+            let application: Retained<AnyObject> = msg_send![application, sharedApplication];
+            let dock_tile: Retained<AnyObject> = msg_send![&application, dockTile];
+            let _: () = msg_send![&dock_tile, setBadgeLabel: label.as_deref()];
+        }
+        Ok(())
+    }
+
+    /// Remembers the badge count returned by `get_badge`
+    fn store_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        *self
+            .inner
+            .badge
+            .lock()
+            .map_err(|_| Error::Other("badge lock poisoned".to_string()))? = count;
+        Ok(())
+    }
+
     /// Creates a completion handler for notification requests
     ///
     /// # Arguments
@@ -343,76 +508,101 @@ impl NotifyManager {
         })
     }
 
+    /// Maps the permission options to the options of the authorization request
+    fn authorization_options(options: PermissionOptions) -> UNAuthorizationOptions {
+        let mut authorization_options = UNAuthorizationOptions::empty();
+        authorization_options.set(UNAuthorizationOptions::Alert, options.alert);
+        authorization_options.set(UNAuthorizationOptions::Sound, options.sound);
+        authorization_options.set(UNAuthorizationOptions::Badge, options.badge);
+        authorization_options.set(UNAuthorizationOptions::Provisional, options.provisional);
+        authorization_options.set(UNAuthorizationOptions::CriticalAlert, options.critical);
+        authorization_options
+    }
+
     /// Requests notification authorization from the user
     ///
     /// # Arguments
+    /// * `options` - What to ask the user to allow
     /// * `sender` - Channel to send the authorization result
     ///
     /// # References
     /// - [UNUserNotificationCenter.requestAuthorizationWithOptions](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/1649527-requestauthorizationwithoptions)
     fn request_notification_authorization(
+        options: PermissionOptions,
         sender: tokio::sync::oneshot::Sender<Result<bool, Error>>,
     ) {
         let block = Self::create_authorization_handler(sender);
 
-        let mut options = UNAuthorizationOptions::empty();
-        options.set(UNAuthorizationOptions::Alert, true);
-        options.set(UNAuthorizationOptions::Sound, true);
-        options.set(UNAuthorizationOptions::Badge, true);
-
         unsafe {
             UNUserNotificationCenter::currentNotificationCenter()
-                .requestAuthorizationWithOptions_completionHandler(options, &block);
+                .requestAuthorizationWithOptions_completionHandler(
+                    Self::authorization_options(options),
+                    &block,
+                );
         }
     }
 
     /// Creates a completion handler for notification settings queries
     ///
     /// # Arguments
-    /// * `sender` - Channel to send the authorization status
+    /// * `sender` - Channel to send the notification settings
     ///
     /// # Returns
     /// A block that processes notification settings
     fn create_settings_handler(
-        sender: tokio::sync::oneshot::Sender<bool>,
+        sender: tokio::sync::oneshot::Sender<NotificationSettings>,
     ) -> block2::RcBlock<dyn Fn(NonNull<UNNotificationSettings>)> {
         let cb = RefCell::new(Some(sender));
 
         block2::RcBlock::new(move |settings: NonNull<UNNotificationSettings>| {
             if let Some(cb) = cb.take() {
-                let auth_status = unsafe { settings.as_ref().authorizationStatus() };
-                let authorized = Self::is_authorization_status_granted(auth_status);
+                let settings = unsafe { settings.as_ref() };
+                let settings = NotificationSettings {
+                    state: Self::permission_state(settings.authorizationStatus()),
+                    alert: Self::feature_setting(settings.alertSetting()),
+                    sound: Self::feature_setting(settings.soundSetting()),
+                    badge: Self::feature_setting(settings.badgeSetting()),
+                    lock_screen: Self::feature_setting(settings.lockScreenSetting()),
+                    disabled_by: None,
+                };
 
-                if cb.send(authorized).is_err() {
+                if cb.send(settings).is_err() {
                     log::error!("The receiver dropped");
                 }
             }
         })
     }
 
-    /// Determines if an authorization status represents granted permission
-    ///
-    /// # Arguments
-    /// * `status` - The authorization status to check
-    ///
-    /// # Returns
-    /// `true` if notifications are authorized, `false` otherwise
+    /// Converts an authorization status to the permission state
     ///
     /// # References
     /// - [UNAuthorizationStatus](https://developer.apple.com/documentation/usernotifications/unauthorizationstatus)
-    fn is_authorization_status_granted(status: UNAuthorizationStatus) -> bool {
+    fn permission_state(status: UNAuthorizationStatus) -> PermissionState {
         match status {
-            UNAuthorizationStatus::Authorized
-            | UNAuthorizationStatus::Provisional
-            | UNAuthorizationStatus::Ephemeral => true,
-            UNAuthorizationStatus::Denied | UNAuthorizationStatus::NotDetermined => false,
+            UNAuthorizationStatus::NotDetermined => PermissionState::NotDetermined,
+            UNAuthorizationStatus::Denied => PermissionState::Denied,
+            UNAuthorizationStatus::Authorized => PermissionState::Authorized,
+            UNAuthorizationStatus::Provisional => PermissionState::Provisional,
+            UNAuthorizationStatus::Ephemeral => PermissionState::Ephemeral,
             _ => {
                 log::error!("Unknown authorization status: {:?}", status);
-                false
+                PermissionState::Denied
             }
         }
     }
 
+    /// Converts the setting of a notification feature
+    ///
+    /// # References
+    /// - [UNNotificationSetting](https://developer.apple.com/documentation/usernotifications/unnotificationsetting)
+    fn feature_setting(setting: UNNotificationSetting) -> FeatureSetting {
+        match setting {
+            UNNotificationSetting::Enabled => FeatureSetting::Enabled,
+            UNNotificationSetting::Disabled => FeatureSetting::Disabled,
+            _ => FeatureSetting::NotSupported,
+        }
+    }
+
     /// Creates a completion handler for retrieving active notifications
     ///
     /// # Arguments
@@ -455,8 +645,22 @@ impl NotifyManager {
             unsafe {
                 let request = item.request();
                 let id = request.identifier().to_string();
-                let user_info = user_info_dictionary_to_hashmap(request.content().userInfo());
-                handles.push(NotifyHandle::new(id, user_info));
+                let content = request.content();
+                let non_empty = |string: Retained<NSString>| {
+                    Some(string.to_string()).filter(|string| !string.is_empty())
+                };
+                handles.push(NotifyHandle::new(
+                    id,
+                    DeliveredContent {
+                        title: non_empty(content.title()),
+                        subtitle: non_empty(content.subtitle()),
+                        body: non_empty(content.body()),
+                        category_id: non_empty(content.categoryIdentifier()),
+                        thread_id: non_empty(content.threadIdentifier()),
+                        user_metadata: user_info_dictionary_to_metadata(content.userInfo()),
+                        delivered_at: ns_date_to_system_time(&item.date()),
+                    },
+                ));
             }
         }
 
@@ -491,8 +695,13 @@ impl NotifyManagerExt for NotifyManager {
 
     /// Checks the current notification permission state
     ///
-    /// # Returns
-    /// `true` if notifications are authorized, `false` otherwise
+    /// # Errors
+    /// Same as [`NotifyManager::get_notification_settings`]
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        Ok(self.get_notification_settings().await?.state)
+    }
+
+    /// Reads the authorization status and the settings of the notification features
     ///
     /// # Errors
     /// - `Error::NoBundleId` if the app doesn't have a valid bundle identifier
@@ -500,10 +709,10 @@ impl NotifyManagerExt for NotifyManager {
     ///
     /// # References
     /// - [UNUserNotificationCenter.getNotificationSettings](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/1649524-getnotificationsettings)
-    async fn get_notification_permission_state(&self) -> Result<bool, Error> {
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
         self.ensure_valid_bundle_id()?;
 
-        let (tx, rx) = tokio::sync::oneshot::channel::<bool>();
+        let (tx, rx) = tokio::sync::oneshot::channel::<NotificationSettings>();
 
         {
             let block = Self::create_settings_handler(tx);
@@ -535,11 +744,118 @@ impl NotifyManagerExt for NotifyManager {
         self.ensure_valid_bundle_id()?;
 
         let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, Error>>();
-        Self::request_notification_authorization(tx);
+        Self::request_notification_authorization(PermissionOptions::default(), tx);
 
         Ok(rx.await??)
     }
 
+    /// Requests notification permission with the given options
+    ///
+    /// Shows the system dialog unless the permission was decided before or `options.provisional`
+    /// is set, then reads the resulting permission state.
+    ///
+    /// # Errors
+    /// Same as [`NotifyManager::first_time_ask_for_notification_permission`]
+    ///
+    /// # References
+    /// - [Use provisional authorization](https://developer.apple.com/documentation/usernotifications/asking-permission-to-use-notifications#Use-provisional-authorization-to-send-trial-notifications)
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.ensure_valid_bundle_id()?;
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<bool, Error>>();
+        Self::request_notification_authorization(options, tx);
+        rx.await??;
+
+        self.get_notification_permission_state().await
+    }
+
+    /// Polls the notification settings, which the user changes in System Settings
+    ///
+    /// The settings are polled instead of re-read when the app becomes active, as that needs
+    /// an `NSApplication`, which the manager does not require, and menu bar apps are rarely
+    /// activated.
+    ///
+    /// # Errors
+    /// Same as [`NotifyManager::get_notification_settings`]
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        let initial = self.get_notification_settings().await?;
+        let manager = self.clone();
+        Ok(poll_permission_changes(
+            initial,
+            PERMISSION_POLL_INTERVAL,
+            move || {
+                let manager = manager.clone();
+                async move { manager.get_notification_settings().await }
+            },
+        ))
+    }
+
+    /// Sets the badge of the dock tile
+    ///
+    /// # Errors
+    /// - `Error::NoBundleId` if the app doesn't have a valid bundle identifier
+    /// - `Error::NotMainThread` before macOS 14 if not called from the main thread, the
+    ///   dock tile of `NSApp` is used there because `setBadgeCount` is missing
+    /// - `Error::NSError` for system-level errors
+    ///
+    /// # References
+    /// - [UNUserNotificationCenter.setBadgeCount](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/setbadgecount(_:withcompletionhandler:))
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        self.ensure_valid_bundle_id()?;
+        let count = count.filter(|count| *count > 0);
+
+        if !UNUserNotificationCenter::class()
+            .responds_to(sel!(setBadgeCount:withCompletionHandler:))
+        {
+            Self::set_dock_tile_badge(count)?;
+            return self.store_badge(count);
+        }
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), Error>>();
+
+        {
+            let block = Self::create_notification_completion_handler(move |result| {
+                if tx.send(result).is_err() {
+                    log::error!("The receiver dropped");
+                }
+            });
+            unsafe {
+                UNUserNotificationCenter::currentNotificationCenter()
+                    .setBadgeCount_withCompletionHandler(
+                        count.unwrap_or_default() as NSInteger,
+                        Some(&block),
+                    );
+            }
+        }
+
+        rx.await??;
+        self.store_badge(count)
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.inner
+            .badge
+            .lock()
+            .map(|badge| *badge)
+            .unwrap_or_default()
+    }
+
+    /// Called by the delegate from `userNotificationCenter:willPresentNotification:`
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.inner.presentation.set_should_present(should_present)
+    }
+
+    /// Ignored, the system only asks the delegate while the app is in the foreground
+    fn set_foreground(&self, _foreground: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Registers notification categories and sets up the response handler
     ///
     /// This method must be called before sending notifications that use
@@ -563,31 +879,23 @@ impl NotifyManagerExt for NotifyManager {
         categories: Vec<NotifyCategory>,
     ) -> Result<(), crate::Error> {
         let mtm = MainThreadMarker::new().ok_or(Error::NotMainThread)?;
-        let (tx, mut rx) =
-            tokio::sync::mpsc::channel::<NotifyResponse>(NOTIFICATION_RESPONSE_CHANNEL_SIZE);
-        let notification_delegate = NotificationDelegate::new(mtm, tx);
-
-        unsafe {
-            // Create and set the delegate
-            let proto: Retained<ProtocolObject<dyn UNUserNotificationCenterDelegate>> =
-                ProtocolObject::from_retained(notification_delegate);
 
-            let notification_center = UNUserNotificationCenter::currentNotificationCenter();
-            notification_center.setDelegate(Some(&*proto));
+        // The delegate is already installed if the manager was created on the main thread
+        if self.inner.delegate_reference.get().is_none() {
+            self.install_delegate(mtm)?;
+        }
 
-            // Store delegate reference to prevent deallocation
-            self.inner
-                .delegate_reference
-                .set(proto)
-                .map_err(|_| Error::MultipleRegisterCalls)?;
+        let mut rx = self
+            .inner
+            .response_receiver
+            .lock()
+            .map_err(|_| Error::Other("response receiver lock poisoned".to_string()))?
+            .take()
+            .ok_or(Error::MultipleRegisterCalls)?;
 
-            // Register notification categories
-            let categories: Retained<NSSet<_>> = categories
-                .into_iter()
-                .map(|category| W(category_to_native_category(category)))
-                .collect();
-            notification_center.setNotificationCategories(&categories);
+        self.set_categories(categories)?;
 
+        unsafe {
             // Start the response handler thread
             let handler_loop = thread::spawn(move || {
                 while let Some(response) = rx.blocking_recv() {
@@ -604,6 +912,50 @@ impl NotifyManagerExt for NotifyManager {
         Ok(())
     }
 
+    /// Replaces the registered notification categories
+    ///
+    /// # References
+    /// - [UNUserNotificationCenter.setNotificationCategories](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/1649512-setnotificationcategories)
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        unsafe {
+            let notification_center = UNUserNotificationCenter::currentNotificationCenter();
+            let categories: Retained<NSSet<_>> = categories
+                .into_iter()
+                .map(|category| W(category_to_native_category(category)))
+                .collect();
+            notification_center.setNotificationCategories(&categories);
+        }
+        Ok(())
+    }
+
+    /// Queues the response of the notification deeplink among the process arguments
+    ///
+    /// # Returns
+    /// The response that was found, if any
+    ///
+    /// # Errors
+    /// - `Error::DeeplinkSignatureMismatch` if the deeplink was forged or tampered with
+    /// - `Error::Other` if the response channel is full
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        let Some(notification_protocol) = &self.notification_protocol else {
+            return Ok(None);
+        };
+
+        let response =
+            deeplink::decode_launch_args(notification_protocol, &args, &self.deeplink_secret)?;
+        if let Some(response) = &response {
+            log::info!(
+                "macOS: Launched by notification {}",
+                response.notification_id
+            );
+            self.inner
+                .response_sender
+                .try_send(response.clone())
+                .map_err(|err| Error::Other(format!("failed to queue launch response: {err}")))?;
+        }
+        Ok(response)
+    }
+
     /// Removes all delivered notifications from the notification center
     ///
     /// # Errors
@@ -678,6 +1030,7 @@ impl NotifyManagerExt for NotifyManager {
     /// - [UNUserNotificationCenter.addNotificationRequest](https://developer.apple.com/documentation/usernotifications/unusernotificationcenter/1649508-addnotificationrequest)
     async fn send(&self, builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
         let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), Error>>();
+        let builder = truncation::truncate(builder, Platform::MacOS);
         let handle = build_and_send(builder, self, tx)?;
         rx.await??;
         Ok(handle)
@@ -688,6 +1041,85 @@ impl NotifyManagerExt for NotifyManager {
 // Utility Functions
 // ============================================================================
 
+/// Converts an `NSDate` to a `SystemTime`, `None` for dates before 1970
+///
+/// # References
+/// - [NSDate.timeIntervalSince1970](https://developer.apple.com/documentation/foundation/nsdate/timeintervalsince1970)
+fn ns_date_to_system_time(date: &NSDate) -> Option<SystemTime> {
+    let seconds = date.timeIntervalSince1970();
+    (seconds >= 0.0).then(|| UNIX_EPOCH + Duration::from_secs_f64(seconds))
+}
+
+/// Converts a UserNotifications userInfo dictionary to the user metadata
+///
+/// This function reads the JSON metadata stored by the builder. Notifications
+/// without it (e.g. sent by previous versions) fall back to the string key-value
+/// pairs of the native NSDictionary.
+///
+/// # Arguments
+/// * `user_info` - The native userInfo dictionary from a notification
+///
+/// # Returns
+/// The user metadata, `null` if the dictionary is empty
+///
+/// # References
+/// - [UNNotificationContent.userInfo](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/1649866-userinfo)
+pub(crate) fn user_info_dictionary_to_metadata(
+    user_info: Retained<NSDictionary<AnyObject, AnyObject>>,
+) -> serde_json::Value {
+    let mut map = user_info_dictionary_to_hashmap(&user_info);
+    map.remove(DEFAULT_URL_KEY);
+    map.remove(PRESENTATION_KEY);
+
+    match map.get(USER_METADATA_JSON_KEY) {
+        Some(json) => serde_json::from_str(json).unwrap_or_else(|err| {
+            log::error!("failed to parse user metadata {json:?}: {err:?}");
+            serde_json::Value::Null
+        }),
+        None if map.is_empty() => serde_json::Value::Null,
+        None => map.into_iter().collect(),
+    }
+}
+
+/// Returns the URL of the first body link, which opens when the notification is clicked
+pub(crate) fn user_info_dictionary_to_default_url(
+    user_info: &NSDictionary<AnyObject, AnyObject>,
+) -> Option<url::Url> {
+    let url = user_info_dictionary_to_hashmap(user_info).remove(DEFAULT_URL_KEY)?;
+    url::Url::parse(&url)
+        .inspect_err(|err| log::error!("failed to parse default url {url:?}: {err:?}"))
+        .ok()
+}
+
+/// Encodes presentation options for the userInfo, e.g. `"banner,sound"`
+pub(crate) fn presentation_to_user_info(presentation: PresentationOptions) -> String {
+    [
+        ("banner", presentation.banner),
+        ("list", presentation.list),
+        ("sound", presentation.sound),
+        ("badge", presentation.badge),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name)
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// Returns the presentation options set on the builder
+pub(crate) fn user_info_dictionary_to_presentation(
+    user_info: &NSDictionary<AnyObject, AnyObject>,
+) -> Option<PresentationOptions> {
+    let presentation = user_info_dictionary_to_hashmap(user_info).remove(PRESENTATION_KEY)?;
+    let flags: Vec<&str> = presentation.split(',').collect();
+    Some(PresentationOptions {
+        banner: flags.contains(&"banner"),
+        list: flags.contains(&"list"),
+        sound: flags.contains(&"sound"),
+        badge: flags.contains(&"badge"),
+    })
+}
+
 /// Converts a UserNotifications userInfo dictionary to a Rust HashMap
 ///
 /// This function safely extracts string key-value pairs from the native
@@ -701,8 +1133,8 @@ impl NotifyManagerExt for NotifyManager {
 ///
 /// # References
 /// - [UNNotificationContent.userInfo](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/1649866-userinfo)
-pub(crate) fn user_info_dictionary_to_hashmap(
-    user_info: Retained<NSDictionary<AnyObject, AnyObject>>,
+fn user_info_dictionary_to_hashmap(
+    user_info: &NSDictionary<AnyObject, AnyObject>,
 ) -> HashMap<String, String> {
     let mut map = HashMap::new();
     let keys = user_info.allKeys();
@@ -749,16 +1181,55 @@ fn category_to_native_category(category: NotifyCategory) -> Retained<UNNotificat
         .map(convert_action_to_native)
         .collect();
 
+    let hidden_preview_body_placeholder = category
+        .hidden_preview_body_placeholder
+        .as_deref()
+        .map(NSString::from_str);
+    let summary_format = category.summary_format.as_deref().map(NSString::from_str);
+
     unsafe {
-        UNNotificationCategory::categoryWithIdentifier_actions_intentIdentifiers_options(
+        UNNotificationCategory::categoryWithIdentifier_actions_intentIdentifiers_hiddenPreviewsBodyPlaceholder_categorySummaryFormat_options(
             &identifier,
             &actions,
             &NSArray::new(),
-            UNNotificationCategoryOptions::empty(),
+            hidden_preview_body_placeholder.as_deref(),
+            summary_format.as_deref(),
+            category_options_to_native(&category),
         )
     }
 }
 
+/// Converts the option flags of a category to native category options
+///
+/// # Arguments
+/// * `category` - The cross-platform category definition
+///
+/// # Returns
+/// The native UNNotificationCategoryOptions
+///
+/// # References
+/// - [UNNotificationCategoryOptions](https://developer.apple.com/documentation/usernotifications/unnotificationcategoryoptions)
+fn category_options_to_native(category: &NotifyCategory) -> UNNotificationCategoryOptions {
+    let mut options = UNNotificationCategoryOptions::empty();
+    options.set(
+        UNNotificationCategoryOptions::CustomDismissAction,
+        category.send_dismiss_action,
+    );
+    options.set(
+        UNNotificationCategoryOptions::AllowInCarPlay,
+        category.allow_in_car_play,
+    );
+    options.set(
+        UNNotificationCategoryOptions::HiddenPreviewsShowTitle,
+        category.hidden_previews_show_title,
+    );
+    options.set(
+        UNNotificationCategoryOptions::HiddenPreviewsShowSubtitle,
+        category.hidden_previews_show_subtitle,
+    );
+    options
+}
+
 /// Converts a single notification action to its native representation
 ///
 /// # Arguments
@@ -855,3 +1326,35 @@ impl<O: Message> FromIterator<W<O>> for Retained<NSSet<O>> {
         set
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorization_options() {
+        assert_eq!(
+            NotifyManager::authorization_options(PermissionOptions::default()),
+            UNAuthorizationOptions::Alert
+                | UNAuthorizationOptions::Sound
+                | UNAuthorizationOptions::Badge
+        );
+        assert_eq!(
+            NotifyManager::authorization_options(PermissionOptions::provisional()),
+            UNAuthorizationOptions::Alert
+                | UNAuthorizationOptions::Sound
+                | UNAuthorizationOptions::Badge
+                | UNAuthorizationOptions::Provisional
+        );
+        assert_eq!(
+            NotifyManager::authorization_options(PermissionOptions {
+                alert: false,
+                sound: false,
+                badge: false,
+                provisional: false,
+                critical: true,
+            }),
+            UNAuthorizationOptions::CriticalAlert
+        );
+    }
+}
@@ -1,6 +1,5 @@
-use crate::{Error, NotifyBuilder, NotifyResponse, NotifyResponseAction};
-use base64::Engine;
-use std::collections::HashMap;
+use crate::{deeplink, Error, NotifyBuilder, NotifyResponse, NotifyResponseAction};
+use url::Url;
 use windows::{core::HSTRING, Data::Xml::Dom::XmlDocument};
 
 /// Builds Windows Toast notification XML from a NotifyBuilder.
@@ -15,7 +14,12 @@ pub fn build_toast_xml(
     builder: NotifyBuilder,
     id: &str,
     notification_protocol: Option<&str>,
-    generate_actions_xml_fn: impl Fn(&str) -> Result<String, Error>,
+    deeplink_secret: &[u8],
+    generate_actions_xml_fn: impl Fn(
+        Option<&str>,
+        &[(String, Url)],
+        &NotifyResponse,
+    ) -> Result<(String, Option<Url>), Error>,
 ) -> Result<XmlDocument, Error> {
     let title_content = builder
         .title
@@ -42,29 +46,49 @@ pub fn build_toast_xml(
         .map(|body| format!(r#"<text id="3">{}</text>"#, quick_xml::escape::escape(body)))
         .unwrap_or_default();
 
-    let launch_options = if let Some(notification_protocol) = notification_protocol {
-        let launch_url = encode_deeplink(
-            notification_protocol,
-            &NotifyResponse {
-                notification_id: id.to_string(),
-                action: NotifyResponseAction::Default,
-                user_input: None,
-                user_metadata: builder.user_metadata.clone().unwrap_or_default(),
-            },
+    let default_response = NotifyResponse {
+        notification_id: id.to_string(),
+        action: NotifyResponseAction::Default,
+        user_input: None,
+        user_metadata: builder.user_metadata.clone().unwrap_or_default(),
+    };
+
+    // Generate actions XML based on category and body links
+    let links = builder.links();
+    let (actions_xml, unplaced_link) = if builder.category_id.is_some() || !links.is_empty() {
+        log::debug!(
+            "Building toast XML with category_id: {:?} and {} links",
+            builder.category_id,
+            links.len()
         );
-        format!(r#"launch="{launch_url}" activationType="protocol""#)
+        let (xml, unplaced_link) =
+            generate_actions_xml_fn(builder.category_id.as_deref(), &links, &default_response)?;
+        log::debug!("Generated actions XML from function: {}", xml);
+        (xml, unplaced_link)
     } else {
-        String::new()
+        log::debug!("No category_id or links provided, no actions will be generated");
+        (String::new(), None)
     };
 
-    // Generate actions XML based on category
-    let actions_xml = if let Some(category_id) = &builder.category_id {
-        log::debug!("Building toast XML with category_id: {}", category_id);
-        let xml = generate_actions_xml_fn(category_id)?;
-        log::debug!("Generated actions XML from function: {}", xml);
-        xml
+    // A link without a button opens when the toast is clicked
+    let launch_response = NotifyResponse {
+        action: unplaced_link
+            .map(NotifyResponseAction::OpenUrl)
+            .unwrap_or(NotifyResponseAction::Default),
+        ..default_response.clone()
+    };
+    let launch_options = if let Some(notification_protocol) = notification_protocol {
+        let launch_url = deeplink::encode(notification_protocol, &launch_response, deeplink_secret);
+        format!(
+            r#"launch="{}" activationType="protocol""#,
+            quick_xml::escape::escape(launch_url)
+        )
+    } else if launch_response.action != NotifyResponseAction::Default {
+        format!(
+            r#"launch="{}""#,
+            quick_xml::escape::escape(deeplink::action_id(&launch_response.action))
+        )
     } else {
-        log::debug!("No category_id provided, no actions will be generated");
         String::new()
     };
 
@@ -83,9 +107,9 @@ pub fn build_toast_xml(
             {actions_xml}
         </toast>"#
     );
-    
+
     log::debug!("Final toast XML: {}", toast_xml_string);
-    
+
     let toast_xml = XmlDocument::new()?;
     toast_xml
         .LoadXml(&HSTRING::from(toast_xml_string))
@@ -93,76 +117,3 @@ pub fn build_toast_xml(
 
     Ok(toast_xml)
 }
-
-/// Encodes a custom protocol deeplink for notification activation.
-///
-/// This creates a URL that can be used to handle notification responses through a custom protocol.
-/// The user metadata is Base64-encoded to safely include complex data in the URL.
-///
-/// # References
-/// - [Launch your app with a URI](https://docs.microsoft.com/en-us/windows/uwp/launch-resume/launch-app-with-uri)
-/// - [Handle app activation](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/send-local-toast-desktop)
-/// - [Base64 encoding specification (RFC 4648)](https://tools.ietf.org/html/rfc4648)
-fn encode_deeplink(scheme: &str, action: &NotifyResponse) -> String {
-    let user_metadata_string = match serde_json::to_string(&action.user_metadata) {
-        Ok(user_metadata_string) => Some(user_metadata_string),
-        Err(err) => {
-            log::error!(
-                "failed to serialize user_metadata: ({:?}) {:?}",
-                action.user_metadata,
-                err
-            );
-            None
-        }
-    }
-    .unwrap_or_else(|| "{}".to_string());
-
-    let attribute = base64::prelude::BASE64_STANDARD.encode(&user_metadata_string);
-
-    let action_string = match &action.action {
-        NotifyResponseAction::Default => "__default__",
-        NotifyResponseAction::Dismiss => "__dismiss__",
-        NotifyResponseAction::Other(action) => action.as_ref(),
-    };
-
-    format!(
-        "{scheme}://{}/{}?{attribute}",
-        action.notification_id, action_string
-    )
-}
-
-/// Decodes a custom protocol deeplink back into a NotifyResponse.
-///
-/// This function parses URLs created by `encode_deeplink()` to extract notification
-/// response information when the application is activated through the protocol handler.
-///
-/// # References
-/// - [url crate documentation](https://docs.rs/url/latest/url/)
-/// - [URL parsing specification (RFC 3986)](https://tools.ietf.org/html/rfc3986)
-/// - [Base64 decoding specification (RFC 4648)](https://tools.ietf.org/html/rfc4648)
-pub fn decode_deeplink(link: &str) -> Result<NotifyResponse, Error> {
-    let url = url::Url::parse(link)?;
-
-    let user_metadata: HashMap<String, String> = match url.query() {
-        None => {
-            log::error!("notification deeplink has no user info");
-            HashMap::new()
-        }
-        Some(base64_userinfo) => {
-            let user_info_str = base64::prelude::BASE64_STANDARD.decode(base64_userinfo)?;
-            serde_json::from_slice(user_info_str.as_slice())
-                .map_err(Error::FailedToParseUserInfo)?
-        }
-    };
-
-    Ok(NotifyResponse {
-        notification_id: url.host().map(|host| host.to_string()).unwrap_or_default(),
-        action: match url.path().to_string().as_str() {
-            "/__default__" => NotifyResponseAction::Default,
-            "/__dismiss__" => NotifyResponseAction::Dismiss,
-            action => NotifyResponseAction::Other(action.to_owned()),
-        },
-        user_input: None,
-        user_metadata,
-    })
-}
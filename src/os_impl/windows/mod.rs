@@ -1,15 +1,23 @@
 use crate::{
-    Error, NotifyBuilder, NotifyCategory, NotifyHandleExt, NotifyManagerExt, NotifyResponseAction,
+    deeplink, poll_permission_changes, truncation, validate_notification_id, DeliveredContent,
+    DisabledBy, Error, FeatureSetting, ForegroundPresentation, NotificationSettings, NotifyBuilder,
+    NotifyCategory, NotifyHandleExt, NotifyManagerExt, NotifyResponse, NotifyResponseAction,
+    PermissionChanges, PermissionOptions, PermissionState, Platform, PresentationOptions,
+    PresentedNotification, Urgency,
 };
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::sync::{Arc, OnceLock, RwLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
 use windows::core::{IInspectable, Interface, HSTRING};
+use windows::Data::Xml::Dom::XmlDocument;
 use windows::Foundation::Collections::StringMap;
 use windows::Foundation::TypedEventHandler;
 use windows::UI::Notifications::{
-    NotificationData, ToastActivatedEventArgs, ToastDismissalReason, ToastDismissedEventArgs,
-    ToastNotifier,
+    BadgeNotification, BadgeUpdateManager, NotificationData, NotificationSetting,
+    ToastActivatedEventArgs, ToastDismissalReason, ToastDismissedEventArgs,
+    ToastNotificationPriority, ToastNotifier,
 };
 use windows::{UI::Notifications::ToastNotification, UI::Notifications::ToastNotificationManager};
 use windows_collections::IVectorView;
@@ -24,10 +32,9 @@ mod builder;
 /// # References
 /// - [Windows Toast Notifications](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/adaptive-interactive-toasts)
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct NotifyHandle {
     id: String,
-    user_metadata: HashMap<String, String>,
+    content: DeliveredContent,
 }
 
 impl NotifyHandleExt for NotifyHandle {
@@ -39,6 +46,34 @@ impl NotifyHandleExt for NotifyHandle {
     fn get_id(&self) -> String {
         self.id.clone()
     }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        self.content.delivered_at
+    }
 }
 
 /// Windows notification manager implementation using Windows Runtime APIs.
@@ -55,7 +90,14 @@ pub struct NotifyManager {
     handler_callback: Arc<OnceLock<Box<dyn Fn(crate::NotifyResponse) + Send + Sync + 'static>>>,
     app_id: String,
     notification_protocol: Option<String>,
+    deeplink_secret: Arc<[u8]>,
     categories: Arc<RwLock<HashMap<String, NotifyCategory>>>,
+    /// Responses that arrived before the handler was registered
+    pending_responses: Arc<Mutex<Vec<NotifyResponse>>>,
+    /// Badge count last set with `set_badge`
+    badge: Arc<Mutex<Option<u32>>>,
+    /// Foreground state and presentation callback
+    presentation: Arc<ForegroundPresentation>,
 }
 
 impl std::fmt::Debug for NotifyManager {
@@ -74,6 +116,14 @@ impl std::fmt::Debug for NotifyManager {
 
 const MESSAGE_GROUP: &str = "msg-group";
 const USER_INFO_JSON_KEY: &str = "UserInfoJson";
+const CATEGORY_ID_KEY: &str = "CategoryId";
+const THREAD_ID_KEY: &str = "ThreadId";
+/// Delivery time in milliseconds since the Unix epoch
+const DELIVERED_AT_KEY: &str = "DeliveredAt";
+/// Toasts show at most 5 buttons
+const MAX_TOAST_BUTTONS: usize = 5;
+/// How often `permission_changes` reads `ToastNotifier.Setting`
+const PERMISSION_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 impl NotifyManager {
     fn new_(app_id: String, notification_protocol: Option<String>) -> Self {
@@ -81,7 +131,11 @@ impl NotifyManager {
             handler_callback: Arc::new(OnceLock::new()),
             app_id,
             notification_protocol,
+            deeplink_secret: deeplink::generate_secret(),
             categories: Arc::new(RwLock::new(HashMap::new())),
+            pending_responses: Arc::new(Mutex::new(Vec::new())),
+            badge: Arc::new(Mutex::new(None)),
+            presentation: Arc::new(ForegroundPresentation::default()),
         }
     }
 
@@ -100,6 +154,18 @@ impl NotifyManager {
         }
     }
 
+    /// Sets the secret used to sign and verify notification protocol deeplinks.
+    ///
+    /// Without it a random secret is generated per manager, so links of notifications
+    /// from a previous session (e.g. cold-start activations) are rejected as tampered.
+    ///
+    /// # References
+    /// - [crate::deeplink]
+    pub fn with_deeplink_secret(mut self, secret: &[u8]) -> Self {
+        self.deeplink_secret = Arc::from(secret);
+        self
+    }
+
     /// Creates a ToastNotifier for the configured app ID.
     ///
     /// # References
@@ -120,16 +186,55 @@ impl NotifyManager {
         Ok(history?)
     }
 
-    fn user_info_from_toast(toast: &ToastNotification) -> Result<HashMap<String, String>, Error> {
+    fn user_info_from_toast(toast: &ToastNotification) -> Result<serde_json::Value, Error> {
         let user_info_string = toast
             .Data()?
             .Values()?
             .Lookup(&HSTRING::from(USER_INFO_JSON_KEY.to_string()))?;
 
-        let user_info: HashMap<String, String> =
-            serde_json::from_str(&quick_xml::escape::unescape(&user_info_string.to_string())?)
-                .map_err(Error::FailedToParseUserInfo)?;
-        Ok(user_info)
+        Self::parse_user_metadata(&user_info_string.to_string())
+    }
+
+    /// Reads the user metadata of a toast, `Value::Null` if it is missing or invalid
+    fn user_metadata_from_toast(toast: &ToastNotification) -> serde_json::Value {
+        Self::user_info_from_toast(toast).unwrap_or_else(|err| {
+            log::error!("failed to read the user metadata of the toast: {err}");
+            serde_json::Value::Null
+        })
+    }
+
+    /// Reads the content of a toast from its XML and data
+    fn content_from_toast(toast: &ToastNotification) -> DeliveredContent {
+        let text = |id: u8| {
+            let xpath = HSTRING::from(format!("//text[@id='{id}']"));
+            toast
+                .Content()
+                .and_then(|xml| xml.SelectSingleNode(&xpath))
+                .and_then(|node| node.InnerText())
+                .ok()
+                .map(|text| text.to_string())
+        };
+        let data = |key: &str| {
+            toast
+                .Data()
+                .and_then(|data| data.Values())
+                .and_then(|values| values.Lookup(&HSTRING::from(key)))
+                .ok()
+                .map(|value| value.to_string())
+        };
+        let delivered_at = data(DELIVERED_AT_KEY)
+            .and_then(|millis| millis.parse().ok())
+            .and_then(|millis| UNIX_EPOCH.checked_add(Duration::from_millis(millis)));
+
+        DeliveredContent {
+            title: text(1),
+            subtitle: text(2),
+            body: text(3),
+            category_id: data(CATEGORY_ID_KEY),
+            thread_id: data(THREAD_ID_KEY),
+            user_metadata: Self::user_metadata_from_toast(toast),
+            delivered_at,
+        }
     }
 
     /// Generate the notification ID for a new notification
@@ -138,17 +243,13 @@ impl NotifyManager {
     }
 
     /// Serialize user metadata to JSON string
-    fn serialize_user_metadata(user_metadata: &Option<HashMap<String, String>>) -> String {
-        user_metadata
-            .as_ref()
-            .and_then(|user_metadata| match serde_json::to_string(user_metadata) {
-                Ok(user_metadata_string) => Some(user_metadata_string),
-                Err(err) => {
-                    log::error!("failed to serialize user_metadata: ({user_metadata:?}) {err:?}");
-                    None
-                }
-            })
-            .unwrap_or("{}".to_string())
+    fn serialize_user_metadata(user_metadata: &Option<serde_json::Value>) -> String {
+        user_metadata.clone().unwrap_or_default().to_string()
+    }
+
+    /// Parse user metadata serialized by `serialize_user_metadata`, it is stored unescaped
+    fn parse_user_metadata(user_metadata_string: &str) -> Result<serde_json::Value, Error> {
+        serde_json::from_str(user_metadata_string).map_err(Error::FailedToParseUserInfo)
     }
 
     /// Create and configure the Windows Toast notification
@@ -162,12 +263,19 @@ impl NotifyManager {
             builder.clone(),
             notification_id,
             self.notification_protocol.as_deref(),
-            |category_id| self.generate_actions_xml(category_id),
+            &self.deeplink_secret,
+            |category_id, links, default_response| {
+                self.generate_actions_xml(category_id, links, default_response)
+            },
         )?;
 
         let toast = ToastNotification::CreateToastNotification(&toast_xml)?;
 
-        self.configure_toast_notification(&toast, notification_id, user_metadata_string)?;
+        self.configure_toast_notification(&toast, builder, notification_id, user_metadata_string)?;
+
+        if builder.urgency == Some(Urgency::Critical) {
+            toast.SetPriority(ToastNotificationPriority::High)?;
+        }
 
         Ok(toast)
     }
@@ -176,6 +284,7 @@ impl NotifyManager {
     fn configure_toast_notification(
         &self,
         toast: &ToastNotification,
+        builder: &NotifyBuilder,
         notification_id: &str,
         user_metadata_string: &str,
     ) -> Result<(), Error> {
@@ -187,6 +296,22 @@ impl NotifyManager {
             &HSTRING::from(USER_INFO_JSON_KEY),
             &HSTRING::from(user_metadata_string),
         )?;
+        for (key, value) in [
+            (CATEGORY_ID_KEY, &builder.category_id),
+            (THREAD_ID_KEY, &builder.thread_id),
+        ] {
+            if let Some(value) = value {
+                user_info_map.Insert(&HSTRING::from(key), &HSTRING::from(value))?;
+            }
+        }
+        let delivered_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        user_info_map.Insert(
+            &HSTRING::from(DELIVERED_AT_KEY),
+            &HSTRING::from(delivered_at.to_string()),
+        )?;
 
         toast.SetData(&NotificationData::CreateNotificationDataWithValues(
             &user_info_map,
@@ -202,20 +327,46 @@ impl NotifyManager {
     ) -> NotifyHandle {
         NotifyHandle {
             id: notification_id,
-            user_metadata: builder.user_metadata.clone().unwrap_or_default(),
+            content: DeliveredContent::from_builder(builder),
         }
     }
 
     /// Generate XML for standard action buttons
-    fn generate_action_xml(identifier: &str, title: &str) -> String {
-        let escaped_identifier = quick_xml::escape::escape(identifier);
+    ///
+    /// With a notification protocol the button activates a deeplink carrying the
+    /// notification id, action id and metadata, so clicks also reach a cold-started app.
+    ///
+    /// # References
+    /// - [Protocol activation](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/toast-schema#toastactivationtype)
+    fn generate_action_xml(
+        &self,
+        action: NotifyResponseAction,
+        title: &str,
+        default_response: &crate::NotifyResponse,
+    ) -> String {
+        let (arguments, activation_type) = match &self.notification_protocol {
+            Some(notification_protocol) => {
+                let response = crate::NotifyResponse {
+                    action,
+                    ..default_response.clone()
+                };
+                (
+                    deeplink::encode(notification_protocol, &response, &self.deeplink_secret),
+                    "protocol",
+                )
+            }
+            None => (deeplink::action_id(&action).into_owned(), "foreground"),
+        };
+        let escaped_arguments = quick_xml::escape::escape(arguments);
         let escaped_title = quick_xml::escape::escape(title);
         format!(
-            r#"<action content="{escaped_title}" arguments="{escaped_identifier}" activationType="foreground" />"#
+            r#"<action content="{escaped_title}" arguments="{escaped_arguments}" activationType="{activation_type}" />"#
         )
     }
 
     /// Generate XML for text input actions
+    ///
+    /// Protocol activation cannot carry the typed text, so these always use foreground activation.
     fn generate_text_input_action_xml(
         identifier: &str,
         input_button_title: &str,
@@ -230,62 +381,88 @@ impl NotifyManager {
         )
     }
 
-    /// Generates action XML elements for notification categories.
+    /// Generates action XML elements for notification categories and body links.
     ///
     /// Creates interactive buttons and input fields for toast notifications based on
-    /// the registered notification categories.
+    /// the registered notification categories, followed by a button per link while
+    /// there is room for it.
+    ///
+    /// # Returns
+    /// The actions XML and the first link that got no button
     ///
     /// # References
     /// - [Toast Actions](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/adaptive-interactive-toasts#actions)
     /// - [Toast Inputs](https://docs.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/adaptive-interactive-toasts#inputs)
-    fn generate_actions_xml(&self, category_id: &str) -> Result<String, Error> {
+    fn generate_actions_xml(
+        &self,
+        category_id: Option<&str>,
+        links: &[(String, Url)],
+        default_response: &crate::NotifyResponse,
+    ) -> Result<(String, Option<Url>), Error> {
         let categories = self.categories.read().map_err(|_| Error::SettingHandler)?;
-
-        log::debug!("Generating actions XML for category: {}", category_id);
-        log::debug!("Available categories: {:?}", categories.keys().collect::<Vec<_>>());
-
-        if let Some(category) = categories.get(category_id) {
-            log::debug!("Found category: {:?}", category);
-            
-            if category.actions.is_empty() {
-                log::debug!("Category has no actions, returning empty XML");
-                return Ok(String::new());
-            }
-
-            let mut actions_xml = String::from("<actions>");
-
-            for action in &category.actions {
-                let action_xml = match action {
-                    crate::NotifyCategoryAction::Action { identifier, title } => {
-                        let xml = Self::generate_action_xml(identifier, title);
-                        log::debug!("Generated action XML: {}", xml);
-                        xml
-                    }
-                    crate::NotifyCategoryAction::TextInputAction {
-                        identifier,
-                        title: _,
-                        input_button_title,
-                        input_placeholder,
-                    } => {
-                        let xml = Self::generate_text_input_action_xml(
+        let mut actions: Vec<String> = Vec::new();
+
+        if let Some(category_id) = category_id {
+            log::debug!("Generating actions XML for category: {}", category_id);
+            log::debug!(
+                "Available categories: {:?}",
+                categories.keys().collect::<Vec<_>>()
+            );
+
+            if let Some(category) = categories.get(category_id) {
+                log::debug!("Found category: {:?}", category);
+
+                for action in &category.actions {
+                    let action_xml = match action {
+                        crate::NotifyCategoryAction::Action { identifier, title } => {
+                            let xml = self.generate_action_xml(
+                                NotifyResponseAction::Other(identifier.clone()),
+                                title,
+                                default_response,
+                            );
+                            log::debug!("Generated action XML: {}", xml);
+                            xml
+                        }
+                        crate::NotifyCategoryAction::TextInputAction {
                             identifier,
+                            title: _,
                             input_button_title,
                             input_placeholder,
-                        );
-                        log::debug!("Generated text input action XML: {}", xml);
-                        xml
-                    }
-                };
-                actions_xml.push_str(&action_xml);
+                        } => {
+                            let xml = Self::generate_text_input_action_xml(
+                                identifier,
+                                input_button_title,
+                                input_placeholder,
+                            );
+                            log::debug!("Generated text input action XML: {}", xml);
+                            xml
+                        }
+                    };
+                    actions.push(action_xml);
+                }
+            } else {
+                log::warn!("Category '{category_id}' not found in registered categories");
             }
+        }
+
+        let link_buttons = MAX_TOAST_BUTTONS.saturating_sub(actions.len());
+        for (label, url) in links.iter().take(link_buttons) {
+            actions.push(self.generate_action_xml(
+                NotifyResponseAction::OpenUrl(url.clone()),
+                label,
+                default_response,
+            ));
+        }
+        let unplaced_link = links.get(link_buttons).map(|(_, url)| url.clone());
 
-            actions_xml.push_str("</actions>");
-            log::debug!("Final actions XML: {}", actions_xml);
-            Ok(actions_xml)
-        } else {
-            log::warn!("Category '{category_id}' not found in registered categories");
-            Ok(String::new())
+        if actions.is_empty() {
+            log::debug!("No actions, returning empty XML");
+            return Ok((String::new(), unplaced_link));
         }
+
+        let actions_xml = format!("<actions>{}</actions>", actions.concat());
+        log::debug!("Final actions XML: {}", actions_xml);
+        Ok((actions_xml, unplaced_link))
     }
 
     /// Extract activated action from toast event arguments
@@ -311,6 +488,28 @@ impl NotifyManager {
         args.as_ref().and_then(|args| args.Reason().ok())
     }
 
+    /// Returns the action of the arguments of an activated toast
+    ///
+    /// With a notification protocol, buttons carry a deeplink. Text input actions and
+    /// buttons without a protocol carry the action id.
+    fn action_from_arguments(
+        arguments: String,
+        notification_protocol: Option<&str>,
+        deeplink_secret: &[u8],
+    ) -> NotifyResponseAction {
+        match notification_protocol {
+            Some(protocol) if arguments.starts_with(&format!("{protocol}:")) => {
+                deeplink::decode(&arguments, deeplink_secret)
+                    .map(|response| response.action)
+                    .inspect_err(|err| {
+                        log::error!("failed to extract action from {arguments}: {err}")
+                    })
+                    .unwrap_or_else(|_| NotifyResponseAction::Other(arguments))
+            }
+            _ => deeplink::action_from_id(arguments),
+        }
+    }
+
     /// Create activation event handler for toast notifications.
     ///
     /// Handles user interactions with toast notifications including button clicks
@@ -322,27 +521,21 @@ impl NotifyManager {
     fn create_activation_handler(
         &self,
         notification_id: String,
-        user_info: HashMap<String, String>,
+        user_info: serde_json::Value,
     ) -> TypedEventHandler<ToastNotification, IInspectable> {
         let handler_callback = self.handler_callback.clone();
         let notification_protocol = self.notification_protocol.clone();
+        let deeplink_secret = self.deeplink_secret.clone();
         TypedEventHandler::new(move |_, insp| {
             let action = Self::get_activated_action(&insp);
             if let Some(handler) = handler_callback.get() {
                 let response_action = action
-                    .map(|action_str| {
-                        // If we have a notification protocol, decode as deeplink
-                        if notification_protocol.is_some() {
-                            builder::decode_deeplink(&action_str)
-                                .map(|response| response.action)
-                                .inspect_err(|err| {
-                                    log::error!("failed to extract action from {action_str}: {err}")
-                                })
-                                .unwrap_or_else(|_| NotifyResponseAction::Other(action_str))
-                        } else {
-                            // Without notification protocol, treat as plain identifier
-                            NotifyResponseAction::Other(action_str)
-                        }
+                    .map(|arguments| {
+                        Self::action_from_arguments(
+                            arguments,
+                            notification_protocol.as_deref(),
+                            &deeplink_secret,
+                        )
                     })
                     .unwrap_or(NotifyResponseAction::Default);
 
@@ -367,23 +560,27 @@ impl NotifyManager {
     fn create_dismissal_handler(
         &self,
         notification_id: String,
-        user_info: HashMap<String, String>,
+        user_info: serde_json::Value,
     ) -> TypedEventHandler<ToastNotification, ToastDismissedEventArgs> {
         let handler_callback = self.handler_callback.clone();
         TypedEventHandler::new(move |_, args| {
             let reason = Self::get_dismissed_reason(&args);
-            match reason {
-                Some(ToastDismissalReason::UserCanceled) => {
-                    if let Some(handler) = handler_callback.get() {
-                        handler(crate::NotifyResponse {
-                            notification_id: notification_id.clone(),
-                            action: NotifyResponseAction::Dismiss,
-                            user_input: None,
-                            user_metadata: user_info.clone(),
-                        })
-                    }
+            let action = match reason {
+                Some(ToastDismissalReason::UserCanceled) => NotifyResponseAction::Dismiss,
+                Some(ToastDismissalReason::TimedOut) => NotifyResponseAction::Expired,
+                Some(ToastDismissalReason::ApplicationHidden) => NotifyResponseAction::Hidden,
+                _ => {
+                    log::debug!("dismissed toast: {reason:?}");
+                    return Ok(());
                 }
-                _ => log::debug!("dismissed toast: {reason:?}"),
+            };
+            if let Some(handler) = handler_callback.get() {
+                handler(crate::NotifyResponse {
+                    notification_id: notification_id.clone(),
+                    action,
+                    user_input: None,
+                    user_metadata: user_info.clone(),
+                })
             }
             Ok(())
         })
@@ -391,7 +588,7 @@ impl NotifyManager {
 
     fn register_event_listeners(&self, toast: &ToastNotification) -> Result<(), Error> {
         let notification_id = toast.Tag()?.to_string();
-        let user_info = Self::user_info_from_toast(toast).unwrap_or_default();
+        let user_info = Self::user_metadata_from_toast(toast);
 
         let activation_handler =
             self.create_activation_handler(notification_id.clone(), user_info.clone());
@@ -402,6 +599,38 @@ impl NotifyManager {
         Ok(())
     }
 
+    /// Deliver a response to the handler, or queue it until the handler is registered
+    fn dispatch_response(&self, response: NotifyResponse) -> Result<(), Error> {
+        let mut pending_responses = self
+            .pending_responses
+            .lock()
+            .map_err(|_| Error::SettingHandler)?;
+        match self.handler_callback.get() {
+            Some(handler) => {
+                drop(pending_responses);
+                handler(response);
+            }
+            None => pending_responses.push(response),
+        }
+        Ok(())
+    }
+
+    /// Deliver the responses that were queued before the handler was registered
+    fn dispatch_pending_responses(&self) -> Result<(), Error> {
+        let pending_responses = std::mem::take(
+            &mut *self
+                .pending_responses
+                .lock()
+                .map_err(|_| Error::SettingHandler)?,
+        );
+        if let Some(handler) = self.handler_callback.get() {
+            for response in pending_responses {
+                handler(response);
+            }
+        }
+        Ok(())
+    }
+
     /// Store notification categories for later use
     fn store_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
         let mut stored_categories = self.categories.write().map_err(|_| Error::SettingHandler)?;
@@ -465,14 +694,82 @@ impl NotifyManager {
 impl NotifyManagerExt for NotifyManager {
     type NotifyHandle = NotifyHandle;
 
-    async fn get_notification_permission_state(&self) -> Result<bool, crate::Error> {
-        Ok(true)
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, crate::Error> {
+        Ok(self.get_notification_settings().await?.state)
+    }
+
+    /// Reads `ToastNotifier.Setting`, Windows has no per-feature settings beyond showing toasts
+    ///
+    /// # References
+    /// - [ToastNotifier.Setting](https://learn.microsoft.com/en-us/uwp/api/windows.ui.notifications.toastnotifier.setting)
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, crate::Error> {
+        notification_settings(&self.app_id)
     }
 
     async fn first_time_ask_for_notification_permission(&self) -> Result<bool, crate::Error> {
         Ok(true)
     }
 
+    /// Windows has no permission prompt, returns the current permission state
+    async fn request_permission(
+        &self,
+        _options: PermissionOptions,
+    ) -> Result<PermissionState, crate::Error> {
+        self.get_notification_permission_state().await
+    }
+
+    /// Polls `ToastNotifier.Setting`, which has no change event
+    async fn permission_changes(&self) -> Result<PermissionChanges, crate::Error> {
+        let initial = notification_settings(&self.app_id)?;
+        let app_id = self.app_id.clone();
+        Ok(poll_permission_changes(
+            initial,
+            PERMISSION_POLL_INTERVAL,
+            move || std::future::ready(notification_settings(&app_id)),
+        ))
+    }
+
+    /// Updates the badge of the taskbar button and start tile
+    ///
+    /// # References
+    /// - [Badge notifications](https://learn.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/badges)
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), crate::Error> {
+        let count = count.filter(|count| *count > 0);
+        let updater = BadgeUpdateManager::CreateBadgeUpdaterForApplicationWithId(&HSTRING::from(
+            &self.app_id,
+        ))?;
+        match count {
+            Some(count) => {
+                let badge_xml = XmlDocument::new()?;
+                badge_xml.LoadXml(&HSTRING::from(format!(r#"<badge value="{count}"/>"#)))?;
+                updater.Update(&BadgeNotification::CreateBadgeNotification(&badge_xml)?)?;
+            }
+            None => updater.Clear()?,
+        }
+        *self
+            .badge
+            .lock()
+            .map_err(|_| Error::Other("badge lock poisoned".to_string()))? = count;
+        Ok(())
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.badge.lock().map(|badge| *badge).unwrap_or_default()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), crate::Error> {
+        self.presentation.set_should_present(should_present)
+    }
+
+    /// Notifications sent in the foreground use the presentation options
+    fn set_foreground(&self, foreground: bool) -> Result<(), crate::Error> {
+        self.presentation.set_foreground(foreground);
+        Ok(())
+    }
+
     fn register(
         &self,
         handler_callback: Box<dyn Fn(crate::NotifyResponse) + Send + Sync + 'static>,
@@ -489,10 +786,39 @@ impl NotifyManagerExt for NotifyManager {
 
         self.store_categories(categories)?;
         self.register_historical_notifications()?;
+        self.dispatch_pending_responses()?;
 
         Ok(())
     }
 
+    fn set_categories(&self, categories: Vec<crate::NotifyCategory>) -> Result<(), crate::Error> {
+        log::info!(
+            "Windows: Replacing notification categories with {} categories",
+            categories.len()
+        );
+        self.store_categories(categories)
+    }
+
+    fn take_launch_response(
+        &self,
+        args: Vec<String>,
+    ) -> Result<Option<NotifyResponse>, crate::Error> {
+        let Some(notification_protocol) = &self.notification_protocol else {
+            return Ok(None);
+        };
+
+        let response =
+            deeplink::decode_launch_args(notification_protocol, &args, &self.deeplink_secret)?;
+        if let Some(response) = &response {
+            log::info!(
+                "Windows: Launched by notification {}",
+                response.notification_id
+            );
+            self.dispatch_response(response.clone())?;
+        }
+        Ok(response)
+    }
+
     fn remove_all_delivered_notifications(&self) -> Result<(), crate::Error> {
         self.clear_all_notifications()
     }
@@ -510,12 +836,9 @@ impl NotifyManagerExt for NotifyManager {
         let mut handles: Vec<NotifyHandle> = Vec::new();
 
         for toast in history.into_iter() {
-            let user_metadata: HashMap<String, String> =
-                Self::user_info_from_toast(&toast).unwrap_or_default();
-
             handles.push(NotifyHandle {
                 id: toast.Tag()?.to_string(),
-                user_metadata,
+                content: Self::content_from_toast(&toast),
             });
         }
 
@@ -527,11 +850,35 @@ impl NotifyManagerExt for NotifyManager {
     async fn send(&self, builder: NotifyBuilder) -> Result<NotifyHandle, crate::Error> {
         log::info!("Windows: Sending notification");
 
-        let notification_id = Self::generate_notification_id();
+        let builder = truncation::truncate(builder, Platform::Windows);
+
+        let notification_id = match &builder.id {
+            Some(id) => {
+                validate_notification_id(id, Platform::Windows)?;
+                id.clone()
+            }
+            None => Self::generate_notification_id(),
+        };
         let user_metadata_string = Self::serialize_user_metadata(&builder.user_metadata);
 
+        let presentation = self
+            .presentation
+            .options_for_builder(&builder, &notification_id);
+        if let Some(PresentationOptions {
+            banner: false,
+            list: false,
+            ..
+        }) = presentation
+        {
+            log::debug!("Windows: not presenting notification {notification_id} in the foreground");
+            return Ok(Self::create_notification_handle(&builder, notification_id));
+        }
+
         let toast =
             self.create_toast_notification(&builder, &notification_id, &user_metadata_string)?;
+        if let Some(presentation) = presentation {
+            toast.SetSuppressPopup(!presentation.banner)?;
+        }
 
         self.register_event_listeners(&toast)?;
         self.get_toast_notifier()?.Show(&toast)?;
@@ -540,3 +887,76 @@ impl NotifyManagerExt for NotifyManager {
         Ok(handle)
     }
 }
+
+/// Reads whether toasts of `app_id` are enabled
+fn notification_settings(app_id: &str) -> Result<NotificationSettings, Error> {
+    let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?;
+    let disabled_by = match notifier.Setting()? {
+        NotificationSetting::Enabled => None,
+        NotificationSetting::DisabledForApplication => Some(DisabledBy::Application),
+        NotificationSetting::DisabledForUser => Some(DisabledBy::User),
+        NotificationSetting::DisabledByGroupPolicy => Some(DisabledBy::GroupPolicy),
+        NotificationSetting::DisabledByManifest => Some(DisabledBy::Manifest),
+        setting => {
+            log::error!("Windows: unknown notification setting {setting:?}");
+            Some(DisabledBy::Application)
+        }
+    };
+    let (state, alert) = match disabled_by {
+        None => (PermissionState::Authorized, FeatureSetting::Enabled),
+        Some(_) => (PermissionState::Denied, FeatureSetting::Disabled),
+    };
+    Ok(NotificationSettings {
+        state,
+        alert,
+        disabled_by,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_metadata_round_trip() {
+        let user_metadata = serde_json::json!({
+            "url": "https://example.com/?a=1&b=2",
+            "nested": {"names": ["A & B", "<b>bold</b>", "&amp;"]},
+        });
+        let string = NotifyManager::serialize_user_metadata(&Some(user_metadata.clone()));
+        assert_eq!(
+            NotifyManager::parse_user_metadata(&string).unwrap(),
+            user_metadata
+        );
+    }
+
+    #[test]
+    fn test_action_from_arguments_with_protocol() {
+        let secret = deeplink::generate_secret();
+        let response = NotifyResponse {
+            notification_id: "id".to_string(),
+            action: NotifyResponseAction::Other("open".to_string()),
+            user_input: None,
+            user_metadata: serde_json::Value::Null,
+        };
+        let button = deeplink::encode("myapp", &response, &secret);
+
+        let action = |arguments: &str| {
+            NotifyManager::action_from_arguments(arguments.to_string(), Some("myapp"), &secret)
+        };
+        assert_eq!(
+            action(&button),
+            NotifyResponseAction::Other("open".to_string())
+        );
+        // text input actions carry the bare identifier
+        assert_eq!(
+            action("reply"),
+            NotifyResponseAction::Other("reply".to_string())
+        );
+        assert_eq!(
+            action(&deeplink::action_id(&NotifyResponseAction::Dismiss)),
+            NotifyResponseAction::Dismiss
+        );
+    }
+}
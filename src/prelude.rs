@@ -1,5 +1,6 @@
 pub use crate::{
     os_impl::{NotifyHandle, NotifyManager},
-    Error as NotifyError, NotifyBuilder, NotifyCategory, NotifyCategoryAction, NotifyHandleExt,
-    NotifyManagerExt, NotifyResponse, NotifyResponseAction,
+    Error as NotifyError, FeatureSetting, NotificationSettings, NotifyBuilder, NotifyCategory,
+    NotifyCategoryAction, NotifyHandleExt, NotifyManagerExt, NotifyResponse, NotifyResponseAction,
+    PermissionOptions, PermissionState, PresentationOptions, PresentedNotification, Urgency,
 };
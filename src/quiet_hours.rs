@@ -0,0 +1,709 @@
+//! Quiet hours
+//!
+//! [`QuietHoursNotifyManager`] wraps a manager and applies a [`QuietHoursPolicy`] while quiet
+//! hours are active: on a schedule of [`QuietRule`]s, e.g. at night and on weekends, or when
+//! turned on manually, e.g. while the user is presenting. Notifications are then suppressed,
+//! deferred or downgraded to [`Urgency::Low`], with exceptions per urgency.
+//!
+//! Deferred notifications are released when quiet hours end, one by one or as a digest.
+//! The end is checked by a task spawned on the tokio runtime of the `send` call.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    DeliveredContent, Error, NotificationSettings, NotifyBuilder, NotifyCategory, NotifyHandleExt,
+    NotifyManagerExt, NotifyResponse, PermissionChanges, PermissionOptions, PermissionState,
+    PresentationOptions, PresentedNotification, Urgency,
+};
+
+/// How often a runtime task checks whether quiet hours ended
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What happens to a notification during quiet hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuietAction {
+    /// Sent as usual
+    Deliver,
+    /// Sent with [`Urgency::Low`]
+    Downgrade,
+    /// Sent when quiet hours end
+    Defer,
+    /// Not sent
+    Suppress,
+}
+
+/// Day of the week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    fn previous(self) -> Self {
+        Self::ALL[(self as usize + 6) % 7]
+    }
+
+    // not a `From` impl, which would make `jiff` part of the public API
+    fn from_jiff(weekday: jiff::civil::Weekday) -> Self {
+        Self::ALL[weekday.to_monday_zero_offset() as usize]
+    }
+}
+
+/// Local time the quiet rules are matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub weekday: Weekday,
+    /// 0 to 23
+    pub hour: u8,
+    /// 0 to 59
+    pub minute: u8,
+}
+
+impl LocalTime {
+    pub fn new(weekday: Weekday, hour: u8, minute: u8) -> Self {
+        Self {
+            weekday,
+            hour,
+            minute,
+        }
+    }
+
+    /// The time in the system time zone
+    pub fn now() -> Self {
+        let now = jiff::Zoned::now();
+        Self::new(
+            Weekday::from_jiff(now.weekday()),
+            now.hour().unsigned_abs(),
+            now.minute().unsigned_abs(),
+        )
+    }
+
+    fn minute_of_day(self) -> u16 {
+        minute_of_day((self.hour, self.minute))
+    }
+}
+
+/// Minutes since midnight of an `(hour, minute)` pair, clamped to 23:59
+fn minute_of_day((hour, minute): (u8, u8)) -> u16 {
+    u16::from(hour.min(23)) * 60 + u16::from(minute.min(59))
+}
+
+/// Time range on days of the week during which quiet hours are active
+///
+/// Times are `(hour, minute)` pairs. A range that ends before it starts continues on the
+/// next day, e.g. 22:00 to 07:00 on Friday ends on Saturday morning. A range that ends when
+/// it starts lasts 24 hours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuietRule {
+    days: Vec<Weekday>,
+    /// Minutes since midnight
+    start: u16,
+    end: u16,
+}
+
+impl QuietRule {
+    /// Quiet from `start` to `end` on the days, hours above 23 and minutes above 59 are clamped
+    pub fn new(days: &[Weekday], start: (u8, u8), end: (u8, u8)) -> Self {
+        Self {
+            days: days.to_vec(),
+            start: minute_of_day(start),
+            end: minute_of_day(end),
+        }
+    }
+
+    /// Quiet from `start` to `end` every day
+    pub fn daily(start: (u8, u8), end: (u8, u8)) -> Self {
+        Self::new(&Weekday::ALL, start, end)
+    }
+
+    /// Quiet all day on Saturday and Sunday
+    pub fn weekends() -> Self {
+        Self::new(&[Weekday::Saturday, Weekday::Sunday], (0, 0), (0, 0))
+    }
+
+    /// Returns whether the rule is active at the local time
+    pub fn contains(&self, time: LocalTime) -> bool {
+        let starts_on = |weekday: Weekday| self.days.contains(&weekday);
+        let (weekday, time) = (time.weekday, time.minute_of_day());
+        if self.start < self.end {
+            starts_on(weekday) && self.start <= time && time < self.end
+        } else {
+            (starts_on(weekday) && time >= self.start)
+                || (starts_on(weekday.previous()) && time < self.end)
+        }
+    }
+}
+
+type Digest = dyn Fn(&[NotifyBuilder]) -> NotifyBuilder + Send + Sync;
+
+/// Schedule and actions of quiet hours
+#[derive(Clone)]
+pub struct QuietHoursPolicy {
+    rules: Vec<QuietRule>,
+    action: QuietAction,
+    urgency_actions: HashMap<Urgency, QuietAction>,
+    digest: Option<Arc<Digest>>,
+}
+
+impl Debug for QuietHoursPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuietHoursPolicy")
+            .field("rules", &self.rules)
+            .field("action", &self.action)
+            .field("urgency_actions", &self.urgency_actions)
+            .field("digest", &self.digest.is_some())
+            .finish()
+    }
+}
+
+impl QuietHoursPolicy {
+    /// Policy that applies `action` to notifications during quiet hours
+    pub fn new(action: QuietAction) -> Self {
+        Self {
+            rules: Vec::new(),
+            action,
+            urgency_actions: HashMap::new(),
+            digest: None,
+        }
+    }
+
+    /// Adds a time range of quiet hours
+    pub fn rule(mut self, rule: QuietRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Applies `action` to notifications of the urgency, e.g. `Deliver` for [`Urgency::Critical`]
+    ///
+    /// Notifications without urgency have [`Urgency::Normal`].
+    pub fn urgency_action(mut self, urgency: Urgency, action: QuietAction) -> Self {
+        self.urgency_actions.insert(urgency, action);
+        self
+    }
+
+    /// Releases several deferred notifications as the notification `digest` builds
+    pub fn digest(
+        mut self,
+        digest: impl Fn(&[NotifyBuilder]) -> NotifyBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.digest = Some(Arc::new(digest));
+        self
+    }
+
+    /// Returns whether a rule is active at the local time
+    pub fn is_scheduled(&self, time: LocalTime) -> bool {
+        self.rules.iter().any(|rule| rule.contains(time))
+    }
+
+    fn action_for(&self, builder: &NotifyBuilder) -> QuietAction {
+        self.urgency_actions
+            .get(&builder.urgency.unwrap_or_default())
+            .copied()
+            .unwrap_or(self.action)
+    }
+}
+
+type Clock = dyn Fn() -> LocalTime + Send + Sync;
+
+#[derive(Debug, Default)]
+struct QuietState {
+    manual: bool,
+    deferred: Vec<NotifyBuilder>,
+    release_scheduled: bool,
+}
+
+/// Manager that holds back notifications during quiet hours
+pub struct QuietHoursNotifyManager<M> {
+    inner: Arc<M>,
+    policy: QuietHoursPolicy,
+    clock: Arc<Clock>,
+    check_interval: Duration,
+    state: Arc<Mutex<QuietState>>,
+}
+
+impl<M: Debug> Debug for QuietHoursNotifyManager<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuietHoursNotifyManager")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<M: NotifyManagerExt + 'static> QuietHoursNotifyManager<M> {
+    pub fn new(inner: M, policy: QuietHoursPolicy) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            policy,
+            clock: Arc::new(LocalTime::now),
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            state: Arc::new(Mutex::new(QuietState::default())),
+        }
+    }
+
+    /// Sets the clock that returns the local time the rules are matched against
+    ///
+    /// By default, the time in the system time zone.
+    pub fn with_clock(mut self, clock: impl Fn() -> LocalTime + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets how often the end of quiet hours is checked while notifications are deferred,
+    /// every minute by default
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Returns the wrapped manager
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> &QuietHoursPolicy {
+        &self.policy
+    }
+
+    /// Turns quiet hours on regardless of the schedule, e.g. while the user is presenting
+    ///
+    /// Deferred notifications are released when quiet hours end, at the latest after the
+    /// check interval.
+    pub fn set_manual(&self, manual: bool) -> Result<(), Error> {
+        lock(&self.state)?.manual = manual;
+        Ok(())
+    }
+
+    /// Returns whether quiet hours are active
+    pub fn is_quiet(&self) -> bool {
+        is_quiet(&self.policy, &*self.clock, &self.state)
+    }
+
+    /// Returns the number of deferred notifications
+    pub fn deferred_count(&self) -> usize {
+        lock(&self.state)
+            .map(|state| state.deferred.len())
+            .unwrap_or_default()
+    }
+
+    /// Sends the deferred notifications now, even during quiet hours
+    pub async fn release(&self) -> Result<(), Error> {
+        release(&*self.inner, &self.policy, &self.state).await
+    }
+}
+
+fn lock(state: &Mutex<QuietState>) -> Result<MutexGuard<'_, QuietState>, Error> {
+    state
+        .lock()
+        .map_err(|_| Error::Other("quiet hours lock poisoned".to_string()))
+}
+
+fn is_quiet(policy: &QuietHoursPolicy, clock: &Clock, state: &Mutex<QuietState>) -> bool {
+    lock(state).is_ok_and(|state| state.manual) || policy.is_scheduled(clock())
+}
+
+/// Sends the deferred notifications, as a digest if the policy has one
+///
+/// Notifications that were not sent stay deferred, ahead of the ones deferred meanwhile.
+async fn release<M: NotifyManagerExt>(
+    inner: &M,
+    policy: &QuietHoursPolicy,
+    state: &Mutex<QuietState>,
+) -> Result<(), Error> {
+    let mut deferred = std::mem::take(&mut lock(state)?.deferred);
+    let sent = match &policy.digest {
+        Some(digest) if deferred.len() > 1 => {
+            let sent = inner.send(digest(&deferred)).await.map(|_| ());
+            if sent.is_ok() {
+                deferred.clear();
+            }
+            sent
+        }
+        _ => {
+            let mut sent = Ok(());
+            while let Some(builder) = deferred.first() {
+                if let Err(err) = inner.send(builder.clone()).await {
+                    sent = Err(err);
+                    break;
+                }
+                deferred.remove(0);
+            }
+            sent
+        }
+    };
+
+    if !deferred.is_empty() {
+        let mut state = lock(state)?;
+        deferred.append(&mut state.deferred);
+        state.deferred = deferred;
+    }
+    sent
+}
+
+/// Releases the deferred notifications when quiet hours end
+async fn release_when_quiet_hours_end<M: NotifyManagerExt>(
+    inner: Arc<M>,
+    policy: QuietHoursPolicy,
+    clock: Arc<Clock>,
+    check_interval: Duration,
+    state: Arc<Mutex<QuietState>>,
+) {
+    loop {
+        tokio::time::sleep(check_interval).await;
+        if is_quiet(&policy, &*clock, &state) {
+            continue;
+        }
+        if let Ok(mut state) = lock(&state) {
+            state.release_scheduled = false;
+        }
+        let Err(err) = release(&*inner, &policy, &state).await else {
+            return;
+        };
+        log::error!("failed to release deferred notifications: {err}");
+
+        // retry at the next check, unless a later deferred notification scheduled a release
+        match lock(&state) {
+            Ok(mut state) if !state.release_scheduled => state.release_scheduled = true,
+            _ => return,
+        }
+    }
+}
+
+/// Handle of [`QuietHoursNotifyManager`]
+#[derive(Debug)]
+pub enum QuietHoursNotifyHandle<H> {
+    /// Sent as usual
+    Sent(H),
+    /// Sent with [`Urgency::Low`] during quiet hours
+    Downgraded(H),
+    /// Held back until quiet hours end
+    Deferred(Box<HeldNotifyHandle>),
+    /// Not sent during quiet hours
+    Suppressed(Box<HeldNotifyHandle>),
+}
+
+impl<H: NotifyHandleExt> QuietHoursNotifyHandle<H> {
+    /// Returns the handle of the wrapped manager if the notification was sent
+    pub fn sent(&self) -> Option<&H> {
+        match self {
+            QuietHoursNotifyHandle::Sent(handle) | QuietHoursNotifyHandle::Downgraded(handle) => {
+                Some(handle)
+            }
+            QuietHoursNotifyHandle::Deferred(_) | QuietHoursNotifyHandle::Suppressed(_) => None,
+        }
+    }
+
+    fn handle(&self) -> &dyn NotifyHandleExt {
+        match self {
+            QuietHoursNotifyHandle::Sent(handle) | QuietHoursNotifyHandle::Downgraded(handle) => {
+                handle
+            }
+            QuietHoursNotifyHandle::Deferred(handle)
+            | QuietHoursNotifyHandle::Suppressed(handle) => handle.as_ref(),
+        }
+    }
+}
+
+impl<H: NotifyHandleExt> NotifyHandleExt for QuietHoursNotifyHandle<H> {
+    fn close(&self) -> Result<(), Error> {
+        self.handle().close()
+    }
+
+    fn get_id(&self) -> String {
+        self.handle().get_id()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.handle().title()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.handle().subtitle()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.handle().body()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.handle().category_id()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.handle().thread_id()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        self.handle().user_metadata()
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        self.handle().delivered_at()
+    }
+}
+
+/// Notification that was deferred or suppressed during quiet hours
+///
+/// Deferred notifications get an id, so they keep it when they are released one by one.
+pub struct HeldNotifyHandle {
+    id: String,
+    content: DeliveredContent,
+    /// Queue of a deferred notification
+    state: Option<Arc<Mutex<QuietState>>>,
+}
+
+impl Debug for HeldNotifyHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeldNotifyHandle")
+            .field("id", &self.id)
+            .field("content", &self.content)
+            .field("deferred", &self.state.is_some())
+            .finish()
+    }
+}
+
+impl NotifyHandleExt for HeldNotifyHandle {
+    /// Drops the notification if it is still deferred
+    fn close(&self) -> Result<(), Error> {
+        if let Some(state) = &self.state {
+            lock(state)?
+                .deferred
+                .retain(|builder| builder.id.as_deref() != Some(self.id.as_str()));
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    /// `None`, the notification was not delivered
+    fn delivered_at(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+#[async_trait]
+impl<M: NotifyManagerExt + 'static> NotifyManagerExt for QuietHoursNotifyManager<M> {
+    type NotifyHandle = QuietHoursNotifyHandle<M::NotifyHandle>;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        self.inner.get_notification_permission_state().await
+    }
+
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
+        self.inner.get_notification_settings().await
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error> {
+        self.inner
+            .first_time_ask_for_notification_permission()
+            .await
+    }
+
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.inner.request_permission(options).await
+    }
+
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        self.inner.permission_changes().await
+    }
+
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        self.inner.set_badge(count).await
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.inner.get_badge()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.inner.set_should_present(should_present)
+    }
+
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error> {
+        self.inner.set_foreground(foreground)
+    }
+
+    fn register(
+        &self,
+        handler_callback: Box<dyn Fn(NotifyResponse) + Send + Sync + 'static>,
+        categories: Vec<NotifyCategory>,
+    ) -> Result<(), Error> {
+        self.inner.register(handler_callback, categories)
+    }
+
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        self.inner.set_categories(categories)
+    }
+
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        self.inner.take_launch_response(args)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), Error> {
+        self.inner.remove_all_delivered_notifications()
+    }
+
+    fn remove_delivered_notifications(&self, ids: Vec<&str>) -> Result<(), Error> {
+        self.inner.remove_delivered_notifications(ids)
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, Error> {
+        Ok(self
+            .inner
+            .get_active_notifications()
+            .await?
+            .into_iter()
+            .map(QuietHoursNotifyHandle::Sent)
+            .collect())
+    }
+
+    async fn send(&self, mut builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
+        let action = if self.is_quiet() {
+            self.policy.action_for(&builder)
+        } else {
+            QuietAction::Deliver
+        };
+
+        match action {
+            QuietAction::Deliver => self
+                .inner
+                .send(builder)
+                .await
+                .map(QuietHoursNotifyHandle::Sent),
+            QuietAction::Downgrade => {
+                builder.urgency = Some(Urgency::Low);
+                self.inner
+                    .send(builder)
+                    .await
+                    .map(QuietHoursNotifyHandle::Downgraded)
+            }
+            QuietAction::Suppress => {
+                let id = builder
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                log::debug!("suppressing notification {id} during quiet hours");
+                Ok(QuietHoursNotifyHandle::Suppressed(Box::new(
+                    HeldNotifyHandle {
+                        id,
+                        content: held_content(&builder),
+                        state: None,
+                    },
+                )))
+            }
+            QuietAction::Defer => {
+                let id = builder
+                    .id
+                    .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                    .clone();
+                let content = held_content(&builder);
+                let schedule_release = {
+                    let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+                        Error::Other("deferring notifications requires a tokio runtime".to_string())
+                    })?;
+                    let mut state = lock(&self.state)?;
+                    state.deferred.push(builder);
+                    let schedule_release = !state.release_scheduled;
+                    state.release_scheduled = true;
+                    schedule_release.then_some(runtime)
+                };
+
+                log::debug!("deferring notification {id} until quiet hours end");
+                if let Some(runtime) = schedule_release {
+                    runtime.spawn(release_when_quiet_hours_end(
+                        self.inner.clone(),
+                        self.policy.clone(),
+                        self.clock.clone(),
+                        self.check_interval,
+                        self.state.clone(),
+                    ));
+                }
+                Ok(QuietHoursNotifyHandle::Deferred(Box::new(
+                    HeldNotifyHandle {
+                        id,
+                        content,
+                        state: Some(self.state.clone()),
+                    },
+                )))
+            }
+        }
+    }
+}
+
+fn held_content(builder: &NotifyBuilder) -> DeliveredContent {
+    DeliveredContent {
+        delivered_at: None,
+        ..DeliveredContent::from_builder(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekday_from_jiff() {
+        // 2025-10-13 is a Monday
+        for (offset, weekday) in Weekday::ALL.into_iter().enumerate() {
+            let date = jiff::civil::date(2025, 10, 13 + offset as i8);
+            assert_eq!(Weekday::from_jiff(date.weekday()), weekday);
+        }
+        assert_eq!(Weekday::Monday.previous(), Weekday::Sunday);
+    }
+}
@@ -0,0 +1,392 @@
+//! Notification templates loaded from TOML or JSON
+//!
+//! A template file defines notification kinds by id, with `{placeholders}` that are
+//! substituted when the template is rendered into a [`NotifyBuilder`]:
+//!
+//! ```toml
+//! [templates.build_finished]
+//! title = "Build {build} finished"
+//! body = "{project} passed all checks"
+//! category = "build"
+//! sound = "default"
+//! urgency = "low"
+//! thread_id = "builds.{project}"
+//! variables = ["build", "project"]
+//! ```
+//!
+//! The JSON format has the same structure, `{"templates": {"build_finished": {…}}}`.
+//! Use `{{` and `}}` for literal braces. Every placeholder has to be listed in `variables`,
+//! templates with placeholders that are not listed are rejected at load time, as are
+//! categories that were not registered. So are translations with unlisted placeholders when
+//! they are rendered.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{localization::Localizer, Error, NotifyBuilder, NotifyCategory, Urgency};
+
+/// Templates as they are written in a template file
+#[derive(Debug, Deserialize)]
+struct TemplateFile {
+    templates: HashMap<String, TemplateDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateDefinition {
+    title: Option<String>,
+    subtitle: Option<String>,
+    body: Option<String>,
+    category: Option<String>,
+    sound: Option<String>,
+    urgency: Option<String>,
+    thread_id: Option<String>,
+    #[serde(default)]
+    variables: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Variable(String),
+}
+
+/// Text with `{placeholders}`, parsed when the template is loaded
+#[derive(Debug, Clone)]
+struct TemplateText {
+    segments: Vec<Segment>,
+}
+
+impl TemplateText {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') => {
+                                name.push(c)
+                            }
+                            Some(c) => {
+                                return Err(format!("invalid character `{c}` in placeholder"))
+                            }
+                            None => return Err(format!("unclosed placeholder `{{{name}`")),
+                        }
+                    }
+                    if name.is_empty() {
+                        return Err("empty placeholder `{}`".to_string());
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Variable(name));
+                }
+                '}' => return Err("unmatched `}`, use `}}` for a literal brace".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn variables(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Variable(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    fn render(&self, template_id: &str, vars: &HashMap<String, String>) -> Result<String, Error> {
+        let mut text = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => text.push_str(literal),
+                Segment::Variable(name) => {
+                    text.push_str(
+                        vars.get(name)
+                            .ok_or_else(|| Error::MissingTemplateVariable {
+                                template: template_id.to_string(),
+                                variable: name.clone(),
+                            })?,
+                    )
+                }
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// A validated notification template
+#[derive(Debug, Clone)]
+struct Template {
+    title: Option<TemplateText>,
+    subtitle: Option<TemplateText>,
+    body: Option<TemplateText>,
+    category: Option<String>,
+    sound: Option<String>,
+    urgency: Option<Urgency>,
+    thread_id: Option<TemplateText>,
+    /// Placeholders the texts and their translations may use
+    variables: HashSet<String>,
+}
+
+impl Template {
+    fn texts(&self) -> impl Iterator<Item = &TemplateText> {
+        [&self.title, &self.subtitle, &self.body, &self.thread_id]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Returns the first placeholder of the text that is not a declared variable
+    fn undeclared_variable<'a>(&self, text: &'a TemplateText) -> Option<&'a str> {
+        text.variables()
+            .find(|name| !self.variables.contains(*name))
+    }
+}
+
+/// Registry of notification templates
+///
+/// # Example
+/// ```no_run
+/// # use std::collections::HashMap;
+/// # use user_notify_reborn::{template::TemplateRegistry, NotifyCategory};
+/// # fn main() -> Result<(), user_notify_reborn::Error> {
+/// let categories = vec![NotifyCategory {
+///     identifier: "build".to_string(),
+///     ..Default::default()
+/// }];
+/// let mut templates = TemplateRegistry::new(&categories);
+/// templates.load_file("notifications.toml")?;
+///
+/// let vars = HashMap::from([
+///     ("build".to_string(), "4711".to_string()),
+///     ("project".to_string(), "gety".to_string()),
+/// ]);
+/// let builder = templates.render("build_finished", &vars)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: HashMap<String, Template>,
+    category_ids: HashSet<String>,
+}
+
+impl TemplateRegistry {
+    /// Creates an empty registry that accepts templates of the given categories
+    pub fn new(categories: &[NotifyCategory]) -> Self {
+        Self {
+            templates: HashMap::new(),
+            category_ids: categories
+                .iter()
+                .map(|category| category.identifier.clone())
+                .collect(),
+        }
+    }
+
+    /// Loads the templates of a `.toml` or `.json` file
+    ///
+    /// # Errors
+    /// - `Error::Io` if the file cannot be read
+    /// - `Error::TemplateParse` if the file cannot be parsed
+    /// - `Error::InvalidTemplate` if a template is invalid, no template of the file is loaded then
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => self.load_toml(&source),
+            Some("json") => self.load_json(&source),
+            _ => Err(Error::TemplateParse(format!(
+                "unsupported template file {path:?}, expected .toml or .json"
+            ))),
+        }
+    }
+
+    /// Loads the templates of a TOML document
+    pub fn load_toml(&mut self, source: &str) -> Result<(), Error> {
+        let file: TemplateFile =
+            toml::from_str(source).map_err(|err| Error::TemplateParse(err.to_string()))?;
+        self.load(file)
+    }
+
+    /// Loads the templates of a JSON document
+    pub fn load_json(&mut self, source: &str) -> Result<(), Error> {
+        let file: TemplateFile =
+            serde_json::from_str(source).map_err(|err| Error::TemplateParse(err.to_string()))?;
+        self.load(file)
+    }
+
+    /// Returns whether a template with the id is loaded
+    pub fn contains(&self, template_id: &str) -> bool {
+        self.templates.contains_key(template_id)
+    }
+
+    /// Renders a template into a notification builder
+    ///
+    /// # Errors
+    /// - `Error::TemplateNotFound` if no template with the id is loaded
+    /// - `Error::MissingTemplateVariable` if a placeholder has no value in `vars`
+    pub fn render(
+        &self,
+        template_id: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<NotifyBuilder, Error> {
+        self.render_with(template_id, vars, |_| None)
+    }
+
+    /// Renders a template with its title, subtitle and body translated into the locale
+    ///
+    /// See [`crate::localization`] for the message ids, texts without a translation are
+    /// rendered as defined in the template.
+    ///
+    /// # Errors
+    /// - `Error::InvalidTemplate` if a translation has invalid placeholders
+    /// - the errors of [`TemplateRegistry::render`]
+    pub fn render_localized(
+        &self,
+        template_id: &str,
+        vars: &HashMap<String, String>,
+        localizer: &dyn Localizer,
+        locale: &str,
+    ) -> Result<NotifyBuilder, Error> {
+        self.render_with(template_id, vars, |field| {
+            localizer.translate(locale, &format!("{template_id}.{field}"))
+        })
+    }
+
+    fn render_with(
+        &self,
+        template_id: &str,
+        vars: &HashMap<String, String>,
+        translate: impl Fn(&str) -> Option<String>,
+    ) -> Result<NotifyBuilder, Error> {
+        let template = self
+            .templates
+            .get(template_id)
+            .ok_or_else(|| Error::TemplateNotFound(template_id.to_string()))?;
+        let render = |text: &Option<TemplateText>, field: &str| {
+            let Some(text) = text else {
+                return Ok(None);
+            };
+            match translate(field) {
+                Some(translation) => {
+                    let invalid = |reason: String| Error::InvalidTemplate {
+                        template: template_id.to_string(),
+                        reason: format!("translation of {field}: {reason}"),
+                    };
+                    let translation = TemplateText::parse(&translation).map_err(invalid)?;
+                    if let Some(undeclared) = template.undeclared_variable(&translation) {
+                        return Err(invalid(format!("undeclared variable `{undeclared}`")));
+                    }
+                    translation.render(template_id, vars)
+                }
+                None => text.render(template_id, vars),
+            }
+            .map(Some)
+        };
+
+        let mut builder = NotifyBuilder::new();
+        if let Some(title) = render(&template.title, "title")? {
+            builder = builder.title(&title);
+        }
+        if let Some(subtitle) = render(&template.subtitle, "subtitle")? {
+            builder = builder.subtitle(&subtitle);
+        }
+        if let Some(body) = render(&template.body, "body")? {
+            builder = builder.body(&body);
+        }
+        if let Some(thread_id) = &template.thread_id {
+            builder = builder.set_thread_id(&thread_id.render(template_id, vars)?);
+        }
+        if let Some(category) = &template.category {
+            builder = builder.set_category_id(category);
+        }
+        if let Some(sound) = &template.sound {
+            builder = builder.sound(sound);
+        }
+        if let Some(urgency) = template.urgency {
+            builder = builder.urgency(urgency);
+        }
+        Ok(builder)
+    }
+
+    /// Validates every template of the file before adding any of them
+    fn load(&mut self, file: TemplateFile) -> Result<(), Error> {
+        let templates = file
+            .templates
+            .into_iter()
+            .map(|(id, definition)| {
+                let template = self.validate(&id, definition)?;
+                Ok((id, template))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.templates.extend(templates);
+        Ok(())
+    }
+
+    fn validate(&self, id: &str, definition: TemplateDefinition) -> Result<Template, Error> {
+        let invalid = |reason: String| Error::InvalidTemplate {
+            template: id.to_string(),
+            reason,
+        };
+        let parse = |text: Option<String>| {
+            text.map(|text| TemplateText::parse(&text))
+                .transpose()
+                .map_err(invalid)
+        };
+
+        if let Some(category) = &definition.category {
+            if !self.category_ids.contains(category) {
+                return Err(invalid(format!("unknown category `{category}`")));
+            }
+        }
+
+        let urgency = definition
+            .urgency
+            .map(|urgency| urgency.parse::<Urgency>())
+            .transpose()
+            .map_err(|err| invalid(err.to_string()))?;
+
+        let template = Template {
+            title: parse(definition.title)?,
+            subtitle: parse(definition.subtitle)?,
+            body: parse(definition.body)?,
+            category: definition.category,
+            sound: definition.sound,
+            urgency,
+            thread_id: parse(definition.thread_id)?,
+            variables: definition.variables.into_iter().collect(),
+        };
+
+        if let Some(undeclared) = template
+            .texts()
+            .find_map(|text| template.undeclared_variable(text))
+        {
+            return Err(invalid(format!("undeclared variable `{undeclared}`")));
+        }
+
+        Ok(template)
+    }
+}
@@ -0,0 +1,612 @@
+//! Rate limiting of sent notifications
+//!
+//! [`ThrottledNotifyManager`] wraps a manager and limits how many notifications of a
+//! category and thread are sent with a token bucket per category and thread. Notifications
+//! over the limit are held back and sent when the bucket has a token again: a single one
+//! unchanged, several as one summary notification, e.g. "12 new messages". Buckets that
+//! refilled completely are dropped whenever a notification is sent.
+//!
+//! Held back notifications are sent from a task spawned on the tokio runtime of the
+//! `send` call, sending fails with `Error::Other` outside of a runtime when over the limit.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use crate::{
+    DeliveredContent, Error, NotificationSettings, NotifyBuilder, NotifyCategory, NotifyHandleExt,
+    NotifyManagerExt, NotifyResponse, PermissionChanges, PermissionOptions, PermissionState,
+    PresentationOptions, PresentedNotification,
+};
+
+/// Token bucket rate limit
+///
+/// Allows `burst` notifications at once and refills one token every `interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    burst: u32,
+    interval: Duration,
+}
+
+impl RateLimit {
+    /// `burst` is at least 1
+    pub fn new(burst: u32, interval: Duration) -> Self {
+        Self {
+            burst: burst.max(1),
+            interval,
+        }
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+/// Category and thread that share a token bucket
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThrottleKey {
+    pub category_id: Option<String>,
+    pub thread_id: Option<String>,
+}
+
+impl ThrottleKey {
+    fn of(builder: &NotifyBuilder) -> Self {
+        Self {
+            category_id: builder.category_id.clone(),
+            thread_id: builder.thread_id.clone(),
+        }
+    }
+}
+
+/// Notifications that were held back by the rate limit and are folded into a summary
+#[derive(Debug, Clone)]
+pub struct Burst {
+    pub key: ThrottleKey,
+    /// Held back notifications in the order they were sent, at least 2
+    pub notifications: Vec<NotifyBuilder>,
+}
+
+type Summarize = dyn Fn(&Burst) -> NotifyBuilder + Send + Sync;
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+    pending: Vec<NotifyBuilder>,
+    flush_scheduled: bool,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            refilled_at: Instant::now(),
+            pending: Vec::new(),
+            flush_scheduled: false,
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at);
+        self.refilled_at = now;
+        if self.limit.interval.is_zero() {
+            self.tokens = self.limit.burst as f64;
+        } else {
+            self.tokens = (self.tokens + elapsed.as_secs_f64() / self.limit.interval.as_secs_f64())
+                .min(self.limit.burst as f64);
+        }
+    }
+
+    fn take_token(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the bucket refilled completely and holds nothing back, a new bucket is the same
+    fn is_idle(&mut self) -> bool {
+        self.refill();
+        self.pending.is_empty() && !self.flush_scheduled && self.tokens >= self.limit.burst as f64
+    }
+
+    /// Time at which the next token is available
+    fn next_token_at(&self) -> Instant {
+        let missing = (1.0 - self.tokens).max(0.0);
+        self.refilled_at + self.limit.interval.mul_f64(missing)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    buckets: HashMap<ThrottleKey, Bucket>,
+}
+
+impl ThrottleState {
+    /// Drops idle buckets, so that e.g. unique thread ids do not keep a bucket each
+    fn remove_idle_buckets(&mut self) {
+        self.buckets.retain(|_, bucket| !bucket.is_idle());
+    }
+}
+
+/// Manager that rate limits the notifications it sends and coalesces bursts
+pub struct ThrottledNotifyManager<M> {
+    inner: Arc<M>,
+    default_limit: RateLimit,
+    category_limits: HashMap<String, RateLimit>,
+    summarize: Arc<Summarize>,
+    state: Arc<Mutex<ThrottleState>>,
+}
+
+impl<M: Debug> Debug for ThrottledNotifyManager<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThrottledNotifyManager")
+            .field("inner", &self.inner)
+            .field("default_limit", &self.default_limit)
+            .field("category_limits", &self.category_limits)
+            .finish()
+    }
+}
+
+impl<M: NotifyManagerExt + 'static> ThrottledNotifyManager<M> {
+    /// Creates a manager that limits every category and thread to `limit`
+    ///
+    /// `summarize` builds the notification that replaces a burst of held back notifications.
+    pub fn new(
+        inner: M,
+        limit: RateLimit,
+        summarize: impl Fn(&Burst) -> NotifyBuilder + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            default_limit: limit,
+            category_limits: HashMap::new(),
+            summarize: Arc::new(summarize),
+            state: Arc::new(Mutex::new(ThrottleState::default())),
+        }
+    }
+
+    /// Sets the limit of a category, instead of the default limit
+    pub fn with_category_limit(mut self, category_id: &str, limit: RateLimit) -> Self {
+        self.category_limits.insert(category_id.to_string(), limit);
+        self
+    }
+
+    /// Returns the wrapped manager
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Returns the number of held back notifications
+    pub fn pending_count(&self) -> usize {
+        lock(&self.state)
+            .map(|state| {
+                state
+                    .buckets
+                    .values()
+                    .map(|bucket| bucket.pending.len())
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sends the held back notifications now, regardless of the rate limit
+    pub async fn flush(&self) -> Result<(), Error> {
+        let bursts: Vec<_> = lock(&self.state)?
+            .buckets
+            .iter_mut()
+            .filter(|(_, bucket)| !bucket.pending.is_empty())
+            .map(|(key, bucket)| (key.clone(), std::mem::take(&mut bucket.pending)))
+            .collect();
+        for (key, pending) in bursts {
+            send_burst(&*self.inner, &*self.summarize, key, pending).await?;
+        }
+        Ok(())
+    }
+
+    fn limit_of(&self, key: &ThrottleKey) -> RateLimit {
+        key.category_id
+            .as_ref()
+            .and_then(|category_id| self.category_limits.get(category_id))
+            .copied()
+            .unwrap_or(self.default_limit)
+    }
+}
+
+fn lock(state: &Mutex<ThrottleState>) -> Result<MutexGuard<'_, ThrottleState>, Error> {
+    state
+        .lock()
+        .map_err(|_| Error::Other("throttle lock poisoned".to_string()))
+}
+
+/// Sends a single held back notification unchanged, several as a summary
+async fn send_burst<M: NotifyManagerExt>(
+    inner: &M,
+    summarize: &Summarize,
+    key: ThrottleKey,
+    mut pending: Vec<NotifyBuilder>,
+) -> Result<(), Error> {
+    let builder = match pending.len() {
+        0 => return Ok(()),
+        1 => pending.remove(0),
+        _ => summarize(&Burst {
+            key,
+            notifications: pending,
+        }),
+    };
+    inner.send(builder).await.map(|_| ())
+}
+
+/// Sends the held back notifications of a bucket whenever it has a token, until none are left
+async fn flush_when_refilled<M: NotifyManagerExt>(
+    inner: Arc<M>,
+    summarize: Arc<Summarize>,
+    state: Arc<Mutex<ThrottleState>>,
+    key: ThrottleKey,
+) {
+    loop {
+        let next_token_at = match lock(&state) {
+            Ok(state) => match state.buckets.get(&key) {
+                Some(bucket) => bucket.next_token_at(),
+                None => return,
+            },
+            Err(_) => return,
+        };
+        tokio::time::sleep_until(next_token_at).await;
+
+        let pending = {
+            let Ok(mut state) = lock(&state) else {
+                return;
+            };
+            let Some(bucket) = state.buckets.get_mut(&key) else {
+                return;
+            };
+            if bucket.pending.is_empty() {
+                bucket.flush_scheduled = false;
+                return;
+            }
+            if !bucket.take_token() {
+                continue;
+            }
+            std::mem::take(&mut bucket.pending)
+        };
+
+        if let Err(err) = send_burst(&*inner, &*summarize, key.clone(), pending).await {
+            log::error!("failed to send held back notifications: {err}");
+        }
+    }
+}
+
+/// Handle of [`ThrottledNotifyManager`]
+#[derive(Debug)]
+pub enum ThrottledNotifyHandle<H> {
+    /// Sent right away
+    Sent(H),
+    /// Held back by the rate limit
+    ///
+    /// Sent when the bucket has a token again, folded into a summary if more were held back.
+    Pending(Box<PendingNotifyHandle>),
+}
+
+impl<H> ThrottledNotifyHandle<H> {
+    /// Returns the handle of the wrapped manager if the notification was sent right away
+    pub fn sent(&self) -> Option<&H> {
+        match self {
+            ThrottledNotifyHandle::Sent(handle) => Some(handle),
+            ThrottledNotifyHandle::Pending(_) => None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self, ThrottledNotifyHandle::Pending(_))
+    }
+}
+
+/// Notification that was held back by the rate limit
+///
+/// Held back notifications get an id, so they keep it when they are sent unchanged.
+pub struct PendingNotifyHandle {
+    id: String,
+    key: ThrottleKey,
+    content: DeliveredContent,
+    state: Arc<Mutex<ThrottleState>>,
+}
+
+impl Debug for PendingNotifyHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingNotifyHandle")
+            .field("id", &self.id)
+            .field("key", &self.key)
+            .field("content", &self.content)
+            .finish()
+    }
+}
+
+impl NotifyHandleExt for PendingNotifyHandle {
+    /// Drops the notification if it is still held back
+    fn close(&self) -> Result<(), Error> {
+        if let Some(bucket) = lock(&self.state)?.buckets.get_mut(&self.key) {
+            bucket
+                .pending
+                .retain(|builder| builder.id.as_deref() != Some(self.id.as_str()));
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.content.title.as_deref()
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        self.content.subtitle.as_deref()
+    }
+
+    fn body(&self) -> Option<&str> {
+        self.content.body.as_deref()
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        self.content.category_id.as_deref()
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        self.content.thread_id.as_deref()
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &self.content.user_metadata
+    }
+
+    /// `None`, the notification was not delivered yet
+    fn delivered_at(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+macro_rules! delegate_handle {
+    ($self:ident, $handle:ident => $call:expr) => {
+        match $self {
+            ThrottledNotifyHandle::Sent($handle) => $call,
+            ThrottledNotifyHandle::Pending($handle) => $call,
+        }
+    };
+}
+
+impl<H: NotifyHandleExt> NotifyHandleExt for ThrottledNotifyHandle<H> {
+    fn close(&self) -> Result<(), Error> {
+        delegate_handle!(self, handle => handle.close())
+    }
+
+    fn get_id(&self) -> String {
+        delegate_handle!(self, handle => handle.get_id())
+    }
+
+    fn title(&self) -> Option<&str> {
+        delegate_handle!(self, handle => handle.title())
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        delegate_handle!(self, handle => handle.subtitle())
+    }
+
+    fn body(&self) -> Option<&str> {
+        delegate_handle!(self, handle => handle.body())
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        delegate_handle!(self, handle => handle.category_id())
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        delegate_handle!(self, handle => handle.thread_id())
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        delegate_handle!(self, handle => handle.user_metadata())
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        delegate_handle!(self, handle => handle.delivered_at())
+    }
+}
+
+#[async_trait]
+impl<M: NotifyManagerExt + 'static> NotifyManagerExt for ThrottledNotifyManager<M> {
+    type NotifyHandle = ThrottledNotifyHandle<M::NotifyHandle>;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, Error> {
+        self.inner.get_notification_permission_state().await
+    }
+
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, Error> {
+        self.inner.get_notification_settings().await
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, Error> {
+        self.inner
+            .first_time_ask_for_notification_permission()
+            .await
+    }
+
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, Error> {
+        self.inner.request_permission(options).await
+    }
+
+    async fn permission_changes(&self) -> Result<PermissionChanges, Error> {
+        self.inner.permission_changes().await
+    }
+
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), Error> {
+        self.inner.set_badge(count).await
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        self.inner.get_badge()
+    }
+
+    fn set_should_present(
+        &self,
+        should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), Error> {
+        self.inner.set_should_present(should_present)
+    }
+
+    fn set_foreground(&self, foreground: bool) -> Result<(), Error> {
+        self.inner.set_foreground(foreground)
+    }
+
+    fn register(
+        &self,
+        handler_callback: Box<dyn Fn(NotifyResponse) + Send + Sync + 'static>,
+        categories: Vec<NotifyCategory>,
+    ) -> Result<(), Error> {
+        self.inner.register(handler_callback, categories)
+    }
+
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), Error> {
+        self.inner.set_categories(categories)
+    }
+
+    fn take_launch_response(&self, args: Vec<String>) -> Result<Option<NotifyResponse>, Error> {
+        self.inner.take_launch_response(args)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), Error> {
+        self.inner.remove_all_delivered_notifications()
+    }
+
+    fn remove_delivered_notifications(&self, ids: Vec<&str>) -> Result<(), Error> {
+        self.inner.remove_delivered_notifications(ids)
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, Error> {
+        Ok(self
+            .inner
+            .get_active_notifications()
+            .await?
+            .into_iter()
+            .map(ThrottledNotifyHandle::Sent)
+            .collect())
+    }
+
+    async fn send(&self, mut builder: NotifyBuilder) -> Result<Self::NotifyHandle, Error> {
+        let key = ThrottleKey::of(&builder);
+        let limit = self.limit_of(&key);
+
+        let pending = {
+            let mut state = lock(&self.state)?;
+            let bucket = state
+                .buckets
+                .entry(key.clone())
+                .or_insert_with(|| Bucket::new(limit));
+            // keep the order, nothing overtakes held back notifications
+            if bucket.pending.is_empty() && bucket.take_token() {
+                state.remove_idle_buckets();
+                None
+            } else {
+                let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+                    Error::Other("holding back notifications requires a tokio runtime".to_string())
+                })?;
+                let id = builder
+                    .id
+                    .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                    .clone();
+                let mut content = DeliveredContent::from_builder(&builder);
+                content.delivered_at = None;
+                bucket.pending.push(builder.clone());
+
+                let schedule_flush = !bucket.flush_scheduled;
+                bucket.flush_scheduled = true;
+                Some((id, content, schedule_flush.then_some(runtime)))
+            }
+        };
+
+        let Some((id, content, flush_runtime)) = pending else {
+            return self
+                .inner
+                .send(builder)
+                .await
+                .map(ThrottledNotifyHandle::Sent);
+        };
+
+        log::debug!("holding back notification {id} of {key:?}");
+        if let Some(runtime) = flush_runtime {
+            runtime.spawn(flush_when_refilled(
+                self.inner.clone(),
+                self.summarize.clone(),
+                self.state.clone(),
+                key.clone(),
+            ));
+        }
+        Ok(ThrottledNotifyHandle::Pending(Box::new(
+            PendingNotifyHandle {
+                id,
+                key,
+                content,
+                state: self.state.clone(),
+            },
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(thread_id: &str) -> ThrottleKey {
+        ThrottleKey {
+            category_id: None,
+            thread_id: Some(thread_id.to_string()),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_remove_idle_buckets() {
+        let limit = RateLimit::new(2, Duration::from_secs(10));
+        let mut state = ThrottleState::default();
+        for thread_id in ["a", "b", "c"] {
+            let bucket = state
+                .buckets
+                .entry(key(thread_id))
+                .or_insert_with(|| Bucket::new(limit));
+            assert!(bucket.take_token());
+        }
+        state
+            .buckets
+            .get_mut(&key("c"))
+            .unwrap()
+            .pending
+            .push(NotifyBuilder::new());
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        state.remove_idle_buckets();
+        assert_eq!(state.buckets.len(), 3);
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        state.remove_idle_buckets();
+        assert_eq!(state.buckets.keys().collect::<Vec<_>>(), [&key("c")]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Text length limits of the platforms and truncation to them
+//!
+//! Notification centers cut long texts after a number of lines, which differs per platform
+//! and text field. [`crate::NotifyBuilder::preview`] returns the texts that a platform likely
+//! shows, cut to the [`TextLimits`] of the platform at grapheme boundaries, preferably between
+//! words, and ending with an ellipsis. With [`Truncation::enabled`] set on the builder, the
+//! texts are also cut like this before the notification is sent.
+//!
+//! The limits approximate the default banner layout of each platform in lines of
+//! graphemes, as the real layout depends on fonts and display scaling.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::NotifyBuilder;
+pub use crate::Platform;
+
+/// Number of lines and graphemes per line a text field can take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineLimit {
+    pub lines: usize,
+    /// Graphemes per line
+    pub width: usize,
+}
+
+/// Text length limits of the fields of a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextLimits {
+    pub title: LineLimit,
+    pub subtitle: LineLimit,
+    pub body: LineLimit,
+    /// Lines taken by the subtitle are not available to the body
+    pub subtitle_shares_body_lines: bool,
+}
+
+impl TextLimits {
+    /// Returns the limits of the default layout of the platform
+    ///
+    /// Windows: title 2 lines, subtitle & body share 4 lines
+    /// macOS: title 1 line, subtitle 1 line, subtitle & body share 4 lines
+    /// Linux: title 1 line, subtitle 1 line, body 5 lines (GNOME Shell)
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Windows => TextLimits {
+                title: LineLimit {
+                    lines: 2,
+                    width: 36,
+                },
+                subtitle: LineLimit {
+                    lines: 4,
+                    width: 44,
+                },
+                body: LineLimit {
+                    lines: 4,
+                    width: 44,
+                },
+                subtitle_shares_body_lines: true,
+            },
+            Platform::MacOS => TextLimits {
+                title: LineLimit {
+                    lines: 1,
+                    width: 36,
+                },
+                subtitle: LineLimit {
+                    lines: 1,
+                    width: 40,
+                },
+                body: LineLimit {
+                    lines: 4,
+                    width: 40,
+                },
+                subtitle_shares_body_lines: true,
+            },
+            Platform::Linux => TextLimits {
+                title: LineLimit {
+                    lines: 1,
+                    width: 40,
+                },
+                subtitle: LineLimit {
+                    lines: 1,
+                    width: 48,
+                },
+                body: LineLimit {
+                    lines: 5,
+                    width: 48,
+                },
+                subtitle_shares_body_lines: false,
+            },
+        }
+    }
+}
+
+/// How texts are cut before a notification is sent, disabled by default
+///
+/// With the `serde` feature, serializes as an object of the fields below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Truncation {
+    /// Cut texts before sending, otherwise the platform cuts them to its real layout
+    pub enabled: bool,
+    /// Appended to cut texts, `…` by default
+    pub ellipsis: String,
+    /// Limits to cut to, the limits of the platform if `None`
+    pub limits: Option<TextLimits>,
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ellipsis: "…".to_string(),
+            limits: None,
+        }
+    }
+}
+
+impl Truncation {
+    /// Cuts texts before sending, they are lost even where the platform would show them
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// Sends texts as they are and leaves cutting to the platform
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Appends the ellipsis to cut texts
+    pub fn ellipsis(mut self, ellipsis: &str) -> Self {
+        self.ellipsis = ellipsis.to_string();
+        self
+    }
+
+    /// Cuts texts to the limits instead of the limits of the platform
+    pub fn limits(mut self, limits: TextLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+}
+
+/// Texts of a notification as a platform shows them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotifyPreview {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: Option<String>,
+    /// Whether any text was cut
+    pub truncated: bool,
+}
+
+/// Cuts the texts of the builder to the limits of the platform
+///
+/// Texts are cut with the ellipsis of the builder's [`Truncation`] even if it is disabled,
+/// as the platform cuts them then.
+pub(crate) fn preview(builder: &NotifyBuilder, platform: Platform) -> NotifyPreview {
+    let truncation = builder.truncation.clone().unwrap_or_default();
+    let limits = truncation
+        .limits
+        .unwrap_or_else(|| TextLimits::for_platform(platform));
+    let ellipsis = truncation.ellipsis.as_str();
+    let mut truncated = false;
+
+    let mut cut = |text: &Option<String>, limit: LineLimit| {
+        let text = text.as_ref()?;
+        let (text, was_cut) = truncate_text(text, limit, ellipsis);
+        truncated |= was_cut;
+        Some(text)
+    };
+
+    let title = cut(&builder.title, limits.title);
+    let subtitle = cut(&builder.subtitle, limits.subtitle);
+    let mut body_limit = limits.body;
+    if limits.subtitle_shares_body_lines {
+        let subtitle_lines = subtitle
+            .as_deref()
+            .map_or(0, |subtitle| line_count(subtitle, limits.subtitle.width));
+        body_limit.lines = body_limit.lines.saturating_sub(subtitle_lines);
+    }
+    let body = match body_limit.lines {
+        0 => {
+            truncated |= builder.body.is_some();
+            None
+        }
+        _ => cut(&builder.body, body_limit),
+    };
+
+    NotifyPreview {
+        title,
+        subtitle,
+        body,
+        truncated,
+    }
+}
+
+/// Returns the builder with its texts cut for the platform if it enables truncation
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub(crate) fn truncate(builder: NotifyBuilder, platform: Platform) -> NotifyBuilder {
+    if !builder
+        .truncation
+        .as_ref()
+        .is_some_and(|truncation| truncation.enabled)
+    {
+        return builder;
+    }
+    let preview = preview(&builder, platform);
+    NotifyBuilder {
+        title: preview.title,
+        subtitle: preview.subtitle,
+        body: preview.body,
+        ..builder
+    }
+}
+
+/// Cuts the text to the limit and appends the ellipsis if it does not fit
+///
+/// The text is cut at a grapheme boundary, between words if one is in the second half
+/// of the last line. Returns the text and whether it was cut.
+pub fn truncate_text(text: &str, limit: LineLimit, ellipsis: &str) -> (String, bool) {
+    let width = limit.width.max(1);
+    let graphemes: Vec<(usize, &str)> = text.grapheme_indices(true).collect();
+    let positions = layout(&graphemes, width);
+
+    let Some(overflow) = positions.iter().position(|(line, _)| *line >= limit.lines) else {
+        return (text.to_string(), false);
+    };
+    if limit.lines == 0 {
+        return (String::new(), true);
+    }
+
+    let last_line = limit.lines - 1;
+    let max_column = width.saturating_sub(ellipsis.graphemes(true).count());
+    let mut end = overflow;
+    while end > 0 && positions[end - 1].0 == last_line && positions[end - 1].1 >= max_column {
+        end -= 1;
+    }
+
+    let line_start = positions[..end]
+        .iter()
+        .position(|(line, _)| *line == last_line)
+        .unwrap_or(end);
+    if let Some(space) = (line_start..end)
+        .rev()
+        .find(|&index| graphemes[index].1.trim().is_empty())
+    {
+        if positions[space].1 >= width / 2 {
+            end = space;
+        }
+    }
+
+    let byte_end = graphemes.get(end).map_or(text.len(), |(index, _)| *index);
+    let mut cut = text[..byte_end].trim_end().to_string();
+    cut.push_str(ellipsis);
+    (cut, true)
+}
+
+/// Returns the line and column of each grapheme, wrapping lines at the width
+fn layout(graphemes: &[(usize, &str)], width: usize) -> Vec<(usize, usize)> {
+    let mut line = 0;
+    let mut column = 0;
+    graphemes
+        .iter()
+        .map(|(_, grapheme)| {
+            if matches!(*grapheme, "\n" | "\r\n") {
+                let position = (line, column);
+                line += 1;
+                column = 0;
+                return position;
+            }
+            if column == width {
+                line += 1;
+                column = 0;
+            }
+            column += 1;
+            (line, column - 1)
+        })
+        .collect()
+}
+
+fn line_count(text: &str, width: usize) -> usize {
+    let graphemes: Vec<(usize, &str)> = text.grapheme_indices(true).collect();
+    layout(&graphemes, width.max(1))
+        .last()
+        .map_or(0, |(line, _)| line + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_is_opt_in() {
+        let title = "Nightly build of the desktop app finished";
+        let builder = NotifyBuilder::new().title(title);
+        assert_eq!(
+            truncate(builder.clone(), Platform::MacOS).get_title(),
+            Some(title)
+        );
+        assert_eq!(
+            truncate(
+                builder.clone().truncation(Truncation::default()),
+                Platform::MacOS
+            )
+            .get_title(),
+            Some(title)
+        );
+        assert_eq!(
+            truncate(builder.truncation(Truncation::enabled()), Platform::MacOS).get_title(),
+            Some("Nightly build of the desktop app…")
+        );
+    }
+}
@@ -0,0 +1,249 @@
+#![allow(dead_code)]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::PermissionChanges;
+
+type Handler = Box<dyn Fn(NotifyResponse) + Send + Sync + 'static>;
+
+/// Manager that keeps what it sends in memory, for testing manager wrappers
+#[derive(Clone, Default)]
+pub struct MockNotifyManager {
+    next_id: Arc<AtomicUsize>,
+    pub sent: Arc<Mutex<Vec<(String, NotifyBuilder)>>>,
+    pub categories: Arc<Mutex<Vec<NotifyCategory>>>,
+    /// Settings the user chose, changes are polled by `permission_changes`
+    pub settings: Arc<Mutex<NotificationSettings>>,
+    /// Makes `send` fail while set
+    pub fail_sends: Arc<AtomicBool>,
+    /// Makes `remove_delivered_notifications` fail while set
+    pub fail_removals: Arc<AtomicBool>,
+    badge: Arc<Mutex<Option<u32>>>,
+    handler: Arc<Mutex<Option<Handler>>>,
+}
+
+impl std::fmt::Debug for MockNotifyManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockNotifyManager")
+            .field("sent", &self.sent)
+            .finish()
+    }
+}
+
+impl MockNotifyManager {
+    /// Delivers a response to the registered handler
+    pub fn respond(&self, notification_id: &str, action: NotifyResponseAction) {
+        let handler = self.handler.lock().unwrap();
+        let handler = handler.as_ref().expect("no handler registered");
+        handler(NotifyResponse {
+            notification_id: notification_id.to_string(),
+            action,
+            user_input: None,
+            user_metadata: serde_json::Value::Null,
+        });
+    }
+
+    /// Returns the title of every sent builder
+    pub fn sent_titles(&self) -> Vec<Option<String>> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, builder)| builder.get_title().map(str::to_string))
+            .collect()
+    }
+
+    /// Returns the id of every sent notification
+    pub fn sent_ids(&self) -> Vec<String> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+/// Handle without content, the sent builder is in [`MockNotifyManager::sent`]
+#[derive(Debug)]
+pub struct MockNotifyHandle {
+    id: String,
+    delivered_at: SystemTime,
+}
+
+impl NotifyHandleExt for MockNotifyHandle {
+    fn close(&self) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    fn get_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn title(&self) -> Option<&str> {
+        None
+    }
+
+    fn subtitle(&self) -> Option<&str> {
+        None
+    }
+
+    fn body(&self) -> Option<&str> {
+        None
+    }
+
+    fn category_id(&self) -> Option<&str> {
+        None
+    }
+
+    fn thread_id(&self) -> Option<&str> {
+        None
+    }
+
+    fn user_metadata(&self) -> &serde_json::Value {
+        &serde_json::Value::Null
+    }
+
+    fn delivered_at(&self) -> Option<SystemTime> {
+        Some(self.delivered_at)
+    }
+}
+
+#[async_trait]
+impl NotifyManagerExt for MockNotifyManager {
+    type NotifyHandle = MockNotifyHandle;
+
+    async fn get_notification_permission_state(&self) -> Result<PermissionState, NotifyError> {
+        Ok(self.settings.lock().unwrap().state)
+    }
+
+    async fn get_notification_settings(&self) -> Result<NotificationSettings, NotifyError> {
+        Ok(*self.settings.lock().unwrap())
+    }
+
+    async fn first_time_ask_for_notification_permission(&self) -> Result<bool, NotifyError> {
+        Ok(self
+            .request_permission(PermissionOptions::default())
+            .await?
+            .is_granted())
+    }
+
+    async fn request_permission(
+        &self,
+        options: PermissionOptions,
+    ) -> Result<PermissionState, NotifyError> {
+        let mut settings = self.settings.lock().unwrap();
+        if settings.state == PermissionState::NotDetermined {
+            settings.state = if options.provisional {
+                PermissionState::Provisional
+            } else {
+                PermissionState::Authorized
+            };
+        }
+        Ok(settings.state)
+    }
+
+    /// Checks the settings every second and yields them when they changed
+    async fn permission_changes(&self) -> Result<PermissionChanges, NotifyError> {
+        let initial = *self.settings.lock().unwrap();
+        Ok(Box::pin(futures_util::stream::unfold(
+            (self.settings.clone(), initial),
+            |(settings, previous)| async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    let current = *settings.lock().unwrap();
+                    if current != previous {
+                        return Some((current, (settings, current)));
+                    }
+                }
+            },
+        )))
+    }
+
+    async fn set_badge(&self, count: Option<u32>) -> Result<(), NotifyError> {
+        *self.badge.lock().unwrap() = count.filter(|count| *count > 0);
+        Ok(())
+    }
+
+    fn get_badge(&self) -> Option<u32> {
+        *self.badge.lock().unwrap()
+    }
+
+    fn set_should_present(
+        &self,
+        _should_present: Box<dyn Fn(&PresentedNotification) -> PresentationOptions + Send + Sync>,
+    ) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    fn set_foreground(&self, _foreground: bool) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    fn register(
+        &self,
+        handler_callback: Handler,
+        categories: Vec<NotifyCategory>,
+    ) -> Result<(), NotifyError> {
+        *self.handler.lock().unwrap() = Some(handler_callback);
+        *self.categories.lock().unwrap() = categories;
+        Ok(())
+    }
+
+    fn set_categories(&self, categories: Vec<NotifyCategory>) -> Result<(), NotifyError> {
+        *self.categories.lock().unwrap() = categories;
+        Ok(())
+    }
+
+    fn take_launch_response(
+        &self,
+        _args: Vec<String>,
+    ) -> Result<Option<NotifyResponse>, NotifyError> {
+        Ok(None)
+    }
+
+    fn remove_all_delivered_notifications(&self) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    fn remove_delivered_notifications(&self, _ids: Vec<&str>) -> Result<(), NotifyError> {
+        if self.fail_removals.load(Ordering::SeqCst) {
+            return Err(NotifyError::NotSupported);
+        }
+        Ok(())
+    }
+
+    async fn get_active_notifications(&self) -> Result<Vec<Self::NotifyHandle>, NotifyError> {
+        Ok(Vec::new())
+    }
+
+    async fn send(&self, builder: NotifyBuilder) -> Result<Self::NotifyHandle, NotifyError> {
+        // like a platform send, lets concurrent sends interleave
+        tokio::task::yield_now().await;
+        if self.fail_sends.load(Ordering::SeqCst) {
+            return Err(NotifyError::Other("send failed".to_string()));
+        }
+        let id = match builder.get_id() {
+            Some(id) => id.to_string(),
+            None => format!("mock-{}", self.next_id.fetch_add(1, Ordering::SeqCst)),
+        };
+        self.sent.lock().unwrap().push((id.clone(), builder));
+        Ok(MockNotifyHandle {
+            id,
+            delivered_at: SystemTime::now(),
+        })
+    }
+}
+
+/// Returns a path in the temp dir that is unique to the test run
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("user-notify-{}-{name}", uuid::Uuid::new_v4()))
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::{sync::atomic::Ordering, time::Duration};
+
+use common::MockNotifyManager;
+use user_notify_reborn::dedupe::{DedupeNotifyManager, DedupePolicy};
+use user_notify_reborn::prelude::*;
+
+fn connection_lost() -> NotifyBuilder {
+    NotifyBuilder::new()
+        .title("Connection lost")
+        .body("Reconnecting…")
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_suppress_duplicates_within_window() {
+    let mock = MockNotifyManager::default();
+    let manager = DedupeNotifyManager::new(
+        mock.clone(),
+        DedupePolicy::suppress(Duration::from_secs(60)),
+    );
+
+    let first = manager.send(connection_lost()).await.unwrap();
+    assert!(!first.is_suppressed());
+
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    let duplicate = manager.send(connection_lost()).await.unwrap();
+    assert!(duplicate.is_suppressed());
+    assert_eq!(duplicate.duplicate_of(), Some(first.get_id().as_str()));
+    assert_eq!(duplicate.title(), Some("Connection lost"));
+
+    let other = manager
+        .send(connection_lost().body("Giving up"))
+        .await
+        .unwrap();
+    assert!(!other.is_suppressed());
+
+    // suppressed duplicates do not extend the window
+    tokio::time::sleep(Duration::from_secs(31)).await;
+    assert!(!manager
+        .send(connection_lost())
+        .await
+        .unwrap()
+        .is_suppressed());
+    assert_eq!(mock.sent.lock().unwrap().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dedupe_key() {
+    let mock = MockNotifyManager::default();
+    let manager = DedupeNotifyManager::new(
+        mock.clone(),
+        DedupePolicy::suppress(Duration::from_secs(60)),
+    );
+
+    manager
+        .send(connection_lost().dedupe_key("connection"))
+        .await
+        .unwrap();
+    let duplicate = manager
+        .send(
+            NotifyBuilder::new()
+                .title("Still offline")
+                .dedupe_key("connection"),
+        )
+        .await
+        .unwrap();
+    assert!(duplicate.is_suppressed());
+
+    manager.reset().unwrap();
+    assert!(!manager
+        .send(connection_lost().dedupe_key("connection"))
+        .await
+        .unwrap()
+        .is_suppressed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replace_duplicates() {
+    let mock = MockNotifyManager::default();
+    let manager =
+        DedupeNotifyManager::new(mock.clone(), DedupePolicy::replace(Duration::from_secs(60)));
+
+    let first = manager.send(connection_lost()).await.unwrap();
+    let replacement = manager.send(connection_lost()).await.unwrap();
+    assert!(!replacement.is_suppressed());
+    assert_eq!(replacement.duplicate_of(), Some(first.get_id().as_str()));
+
+    let sent = mock.sent.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+    let (id, builder) = &sent[1];
+    assert_eq!(*id, first.get_id());
+    assert_eq!(builder.get_id(), Some(first.get_id().as_str()));
+    assert_eq!(builder.get_title(), Some("Connection lost"));
+    assert_eq!(builder.get_body(), Some("Reconnecting…"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_duplicates_are_suppressed() {
+    let mock = MockNotifyManager::default();
+    let manager = DedupeNotifyManager::new(
+        mock.clone(),
+        DedupePolicy::suppress(Duration::from_secs(60)),
+    );
+
+    let (first, second) = tokio::join!(
+        manager.send(connection_lost()),
+        manager.send(connection_lost())
+    );
+    let (first, second) = (first.unwrap(), second.unwrap());
+
+    assert!(!first.is_suppressed());
+    assert!(second.is_suppressed());
+    assert_eq!(second.duplicate_of(), Some(first.get_id().as_str()));
+    assert_eq!(mock.sent_ids(), [first.get_id()]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_send_is_not_a_duplicate() {
+    let mock = MockNotifyManager::default();
+    let manager = DedupeNotifyManager::new(
+        mock.clone(),
+        DedupePolicy::suppress(Duration::from_secs(60)),
+    );
+
+    mock.fail_sends.store(true, Ordering::SeqCst);
+    assert!(manager.send(connection_lost()).await.is_err());
+
+    mock.fail_sends.store(false, Ordering::SeqCst);
+    assert!(!manager
+        .send(connection_lost())
+        .await
+        .unwrap()
+        .is_suppressed());
+    assert_eq!(mock.sent.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replace_when_removal_fails() {
+    let mock = MockNotifyManager::default();
+    let manager =
+        DedupeNotifyManager::new(mock.clone(), DedupePolicy::replace(Duration::from_secs(60)));
+
+    manager
+        .send(connection_lost().dedupe_key("connection"))
+        .await
+        .unwrap();
+    mock.fail_removals.store(true, Ordering::SeqCst);
+    let replacement = manager
+        .send(
+            connection_lost()
+                .id("connection-2")
+                .unwrap()
+                .dedupe_key("connection"),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(replacement.get_id(), "connection-2");
+    assert_eq!(mock.sent_ids()[1], "connection-2");
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use proptest::prelude::*;
+use serde::Deserialize;
+use user_notify_reborn::deeplink;
+use user_notify_reborn::prelude::*;
+
+const SECRET: &[u8] = b"deeplink-test-secret";
+
+fn test_response() -> NotifyResponse {
+    NotifyResponse {
+        notification_id: "0123456789abcdef".to_string(),
+        action: NotifyResponseAction::Other("reply".to_string()),
+        user_input: None,
+        user_metadata: serde_json::json!({ "chat": { "id": 42, "muted": false } }),
+    }
+}
+
+#[test]
+fn test_deeplink_round_trip() {
+    let link = deeplink::encode("myapp", &test_response(), SECRET);
+    let response = deeplink::decode(&link, SECRET).expect("failed to decode deeplink");
+
+    assert_eq!(response.notification_id, "0123456789abcdef");
+    assert_eq!(
+        response.action,
+        NotifyResponseAction::Other("reply".to_string())
+    );
+    assert_eq!(response.user_metadata, test_response().user_metadata);
+}
+
+#[test]
+fn test_deeplink_typed_metadata() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Chat {
+        id: u64,
+        muted: bool,
+    }
+
+    let link = deeplink::encode("myapp", &test_response(), SECRET);
+    let response = deeplink::decode(&link, SECRET).expect("failed to decode deeplink");
+    let metadata: HashMap<String, Chat> = response.metadata().expect("failed to parse metadata");
+
+    assert_eq!(
+        metadata["chat"],
+        Chat {
+            id: 42,
+            muted: false
+        }
+    );
+}
+
+#[test]
+fn test_deeplink_is_url_safe() {
+    let mut response = test_response();
+    response.action = NotifyResponseAction::Other("app.category/reply?now#1".to_string());
+    // serializes to JSON whose Base64 contains `+` and `/` in the standard alphabet
+    response.user_metadata = serde_json::json!({ "k": "~~~???>>>" });
+
+    let link = deeplink::encode("myapp", &response, SECRET);
+    let query = link.split_once('?').map(|(_, query)| query).unwrap();
+
+    // standard Base64 would leave `+` or `/` in the query or need them percent-encoded
+    assert!(!query.contains(['+', '/', '%']), "{link}");
+    assert!(link.contains("/app.category%2Freply%3Fnow%231?"), "{link}");
+    assert_eq!(
+        deeplink::decode(&link, SECRET).unwrap().action,
+        response.action
+    );
+}
+
+#[test]
+fn test_deeplink_open_url_action() {
+    let mut response = test_response();
+    response.action = NotifyResponseAction::OpenUrl(
+        url::Url::parse("https://example.com/pr/123?tab=files#diff").unwrap(),
+    );
+
+    let link = deeplink::encode("myapp", &response, SECRET);
+    assert_eq!(
+        deeplink::decode(&link, SECRET).unwrap().action,
+        response.action
+    );
+}
+
+#[test]
+fn test_deeplink_rejects_tampered_link() {
+    let link = deeplink::encode("myapp", &test_response(), SECRET);
+    let tampered = link.replace("/reply?", "/delete?");
+
+    assert!(matches!(
+        deeplink::decode(&tampered, SECRET),
+        Err(NotifyError::DeeplinkSignatureMismatch)
+    ));
+}
+
+#[test]
+fn test_deeplink_rejects_wrong_secret() {
+    let link = deeplink::encode("myapp", &test_response(), SECRET);
+
+    assert!(matches!(
+        deeplink::decode(&link, b"another-secret"),
+        Err(NotifyError::DeeplinkSignatureMismatch)
+    ));
+}
+
+#[test]
+fn test_deeplink_rejects_unversioned_link() {
+    assert!(matches!(
+        deeplink::decode("myapp://0123456789abcdef/__default__?e30", SECRET),
+        Err(NotifyError::InvalidDeeplink(_))
+    ));
+}
+
+#[test]
+fn test_decode_launch_args() {
+    let link = deeplink::encode("myapp", &test_response(), SECRET);
+    let args = vec!["C:\\Program Files\\MyApp\\myapp.exe".to_string(), link];
+
+    let response = deeplink::decode_launch_args("myapp", &args, SECRET)
+        .expect("failed to decode launch args")
+        .expect("no launch response found");
+    assert_eq!(response.notification_id, "0123456789abcdef");
+
+    assert!(deeplink::decode_launch_args("myapp", &args[..1], SECRET)
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        deeplink::decode_launch_args("myapp", &args, b"another-secret"),
+        Err(NotifyError::DeeplinkSignatureMismatch)
+    ));
+}
+
+fn action_strategy() -> impl Strategy<Value = NotifyResponseAction> {
+    prop_oneof![
+        Just(NotifyResponseAction::Default),
+        Just(NotifyResponseAction::Dismiss),
+        Just(NotifyResponseAction::Expired),
+        Just(NotifyResponseAction::Hidden),
+        any::<String>()
+            .prop_filter("reserved action ids", |action| !action.starts_with("__"))
+            .prop_map(NotifyResponseAction::Other),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_deeplink_round_trip_any_response(
+        notification_id in any::<String>(),
+        action in action_strategy(),
+        user_metadata in any::<HashMap<String, String>>(),
+    ) {
+        let response = NotifyResponse {
+            notification_id,
+            action,
+            user_input: None,
+            user_metadata: serde_json::to_value(user_metadata).unwrap(),
+        };
+
+        let link = deeplink::encode("myapp", &response, SECRET);
+        let decoded = deeplink::decode(&link, SECRET).unwrap();
+
+        prop_assert_eq!(decoded.notification_id, response.notification_id);
+        prop_assert_eq!(decoded.action, response.action);
+        prop_assert_eq!(decoded.user_metadata, response.user_metadata);
+    }
+}
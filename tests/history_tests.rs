@@ -0,0 +1,217 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+
+use common::{temp_path, MockNotifyManager};
+use user_notify_reborn::history::{
+    HistoryNotifyManager, HistoryQuery, HistoryRetention, NotificationHistory,
+};
+use user_notify_reborn::prelude::*;
+
+fn builder(title: &str, category: &str, thread: &str) -> NotifyBuilder {
+    NotifyBuilder::new()
+        .title(title)
+        .body("body")
+        .set_category_id(category)
+        .set_thread_id(thread)
+}
+
+#[test]
+fn test_history_persists_across_opens() {
+    let path = temp_path("history.jsonl");
+    let history = NotificationHistory::open(&path).unwrap();
+    history
+        .record_sent("n1", &builder("First", "build", "builds"))
+        .unwrap();
+    history
+        .record_response(&NotifyResponse {
+            notification_id: "n1".to_string(),
+            action: NotifyResponseAction::Other("open".to_string()),
+            user_input: Some("typed".to_string()),
+            user_metadata: serde_json::Value::Null,
+        })
+        .unwrap();
+    drop(history);
+
+    let history = NotificationHistory::open(&path).unwrap();
+    let item = history.get("n1").unwrap().expect("n1 not in history");
+    assert_eq!(item.title.as_deref(), Some("First"));
+    assert_eq!(item.category_id.as_deref(), Some("build"));
+    assert_eq!(item.responses.len(), 1);
+    assert_eq!(
+        item.responses[0].action,
+        NotifyResponseAction::Other("open".to_string())
+    );
+    assert_eq!(item.responses[0].user_input.as_deref(), Some("typed"));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_history_get_repeated_id() {
+    let path = temp_path("history.jsonl");
+    let history = NotificationHistory::open(&path).unwrap();
+    history
+        .record_sent("build-4711", &builder("Build running", "build", "builds"))
+        .unwrap();
+    history
+        .record_sent("build-4711", &builder("Build finished", "build", "builds"))
+        .unwrap();
+    history
+        .record_response(&NotifyResponse {
+            notification_id: "build-4711".to_string(),
+            action: NotifyResponseAction::Default,
+            user_input: None,
+            user_metadata: serde_json::Value::Null,
+        })
+        .unwrap();
+
+    let item = history.get("build-4711").unwrap().unwrap();
+    assert_eq!(item.title.as_deref(), Some("Build finished"));
+    assert_eq!(item.responses.len(), 1);
+    assert_eq!(item.responses[0].action, NotifyResponseAction::Default);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_history_query() {
+    let path = temp_path("history.jsonl");
+    let history = NotificationHistory::open(&path).unwrap();
+    let before = SystemTime::now() - Duration::from_secs(1);
+    history
+        .record_sent("n1", &builder("Build 1", "build", "builds"))
+        .unwrap();
+    history
+        .record_sent("n2", &builder("Chat", "chat", "chat.42"))
+        .unwrap();
+    history
+        .record_sent("n3", &builder("Build 2", "build", "builds"))
+        .unwrap();
+
+    let ids = |query: HistoryQuery| -> Vec<String> {
+        history
+            .query(&query)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.notification_id)
+            .collect()
+    };
+
+    assert_eq!(ids(HistoryQuery::new()), ["n3", "n2", "n1"]);
+    assert_eq!(ids(HistoryQuery::new().category_id("build")), ["n3", "n1"]);
+    assert_eq!(ids(HistoryQuery::new().thread_id("chat.42")), ["n2"]);
+    assert_eq!(ids(HistoryQuery::new().limit(1)), ["n3"]);
+    assert!(ids(HistoryQuery::new().until(before)).is_empty());
+    assert_eq!(ids(HistoryQuery::new().since(before)).len(), 3);
+
+    history.clear().unwrap();
+    assert!(ids(HistoryQuery::new()).is_empty());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_history_skips_corrupt_lines() {
+    let path = temp_path("history.jsonl");
+    std::fs::write(
+        &path,
+        "not json\n{\"type\":\"sent\",\"notification_id\":\"n1\",\"timestamp_ms\":1000,\"title\":\"Old\",\"subtitle\":null,\"body\":null,\"category_id\":null,\"thread_id\":null}\n",
+    )
+    .unwrap();
+
+    let history = NotificationHistory::open(&path).unwrap();
+    let item = history.get("n1").unwrap().unwrap();
+    assert_eq!(item.title.as_deref(), Some("Old"));
+    assert_eq!(
+        item.sent_at,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1)
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_history_retention_max_entries() {
+    let path = temp_path("history.jsonl");
+    let history = NotificationHistory::open(&path)
+        .unwrap()
+        .with_retention(HistoryRetention::new().max_entries(2))
+        .unwrap();
+    for n in 0..10 {
+        history
+            .record_sent(&format!("n{n}"), &builder("Build", "build", "builds"))
+            .unwrap();
+    }
+
+    let ids = |history: &NotificationHistory| {
+        history
+            .query(&HistoryQuery::new())
+            .unwrap()
+            .into_iter()
+            .map(|item| item.notification_id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&history), ["n9", "n8"]);
+    drop(history);
+
+    // the file was compacted, so it does not hold every sent notification
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert!(lines <= 4, "{lines} lines");
+    let history = NotificationHistory::open(&path)
+        .unwrap()
+        .with_retention(HistoryRetention::new().max_entries(2))
+        .unwrap();
+    assert_eq!(ids(&history), ["n9", "n8"]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_history_retention_max_age() {
+    let path = temp_path("history.jsonl");
+    std::fs::write(
+        &path,
+        "{\"type\":\"sent\",\"notification_id\":\"old\",\"timestamp_ms\":1000,\"title\":\"Old\",\"subtitle\":null,\"body\":null,\"category_id\":null,\"thread_id\":null}\n",
+    )
+    .unwrap();
+
+    let history = NotificationHistory::open(&path)
+        .unwrap()
+        .with_retention(HistoryRetention::new().max_age(Duration::from_secs(24 * 60 * 60)))
+        .unwrap();
+    assert!(history.get("old").unwrap().is_none());
+    history
+        .record_sent("new", &builder("New", "build", "builds"))
+        .unwrap();
+    drop(history);
+
+    let history = NotificationHistory::open(&path).unwrap();
+    assert!(history.get("old").unwrap().is_none());
+    assert!(history.get("new").unwrap().is_some());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_history_manager_records_sends_and_responses() {
+    let path = temp_path("history.jsonl");
+    let mock = MockNotifyManager::default();
+    let manager =
+        HistoryNotifyManager::new(mock.clone(), NotificationHistory::open(&path).unwrap());
+    manager.register(Box::new(|_| {}), vec![]).unwrap();
+
+    let handle = manager
+        .send(builder("Build", "build", "builds"))
+        .await
+        .unwrap();
+    mock.respond(&handle.get_id(), NotifyResponseAction::Dismiss);
+
+    let items = manager.history().query(&HistoryQuery::new()).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].notification_id, handle.get_id());
+    assert_eq!(items[0].responses[0].action, NotifyResponseAction::Dismiss);
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,182 @@
+#![cfg(target_os = "linux")]
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+
+use futures_util::StreamExt;
+use user_notify_reborn::prelude::*;
+use zbus::{message::Type, zvariant::OwnedValue, MatchRule, MessageStream};
+
+/// Private session bus, killed on drop
+struct LocalBus {
+    daemon: Child,
+    address: String,
+}
+
+impl LocalBus {
+    /// Returns `None` if `dbus-daemon` is not installed
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--print-address=1", "--nofork"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+
+    async fn connect(&self) -> zbus::Connection {
+        zbus::connection::Builder::address(self.address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+    }
+}
+
+impl Drop for LocalBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+#[tokio::test]
+async fn test_launcher_entry_badge() {
+    let Some(bus) = LocalBus::start() else {
+        eprintln!("dbus-daemon not found, skipping");
+        return;
+    };
+
+    let dock = bus.connect().await;
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface("com.canonical.Unity.LauncherEntry")
+        .unwrap()
+        .member("Update")
+        .unwrap()
+        .build();
+    let mut updates = MessageStream::for_match_rule(rule, &dock, None)
+        .await
+        .unwrap();
+
+    let manager = NotifyManager::try_new("org.example.App", None)
+        .unwrap()
+        .with_connection(bus.connect().await);
+    assert_eq!(manager.get_badge(), None);
+
+    for (count, expected) in [(Some(3), 3), (Some(0), 0), (None, 0)] {
+        manager.set_badge(count).await.unwrap();
+        let update = updates.next().await.unwrap().unwrap();
+        let (app_uri, properties): (String, HashMap<String, OwnedValue>) =
+            update.body().deserialize().unwrap();
+        assert_eq!(app_uri, "application://org.example.App.desktop");
+        assert_eq!(i64::try_from(&properties["count"]).unwrap(), expected);
+        assert_eq!(
+            bool::try_from(&properties["count-visible"]).unwrap(),
+            expected > 0
+        );
+        assert_eq!(manager.get_badge(), count.filter(|count| *count > 0));
+    }
+}
+
+#[tokio::test]
+async fn test_badge_requires_bundle_id() {
+    let manager = NotifyManager::new_();
+    assert!(matches!(
+        manager.set_badge(Some(1)).await,
+        Err(NotifyError::NoBundleId)
+    ));
+}
+
+#[tokio::test]
+async fn test_foreground_presentation() {
+    let manager = NotifyManager::new_();
+    manager
+        .set_should_present(Box::new(|notification| {
+            if notification.category_id.as_deref() == Some("chat") {
+                PresentationOptions::none()
+            } else {
+                PresentationOptions::default()
+            }
+        }))
+        .unwrap();
+    let chat = || {
+        NotifyBuilder::new()
+            .title("Message")
+            .set_category_id("chat")
+    };
+
+    assert!(matches!(
+        manager.send(chat()).await,
+        Err(NotifyError::NotSupported)
+    ));
+
+    manager.set_foreground(true).unwrap();
+    let handle = manager.send(chat().id("message-1").unwrap()).await.unwrap();
+    assert_eq!(handle.get_id(), "message-1");
+    assert_eq!(handle.title(), Some("Message"));
+    assert!(matches!(
+        manager.send(NotifyBuilder::new().title("Mail")).await,
+        Err(NotifyError::NotSupported)
+    ));
+}
+
+#[tokio::test]
+async fn test_closed_notifications() {
+    let Some(bus) = LocalBus::start() else {
+        eprintln!("dbus-daemon not found, skipping");
+        return;
+    };
+
+    let server = bus.connect().await;
+    server
+        .request_name("org.freedesktop.Notifications")
+        .await
+        .unwrap();
+    let manager = NotifyManager::new_().with_connection(bus.connect().await);
+    let mut closed = manager.closed_notifications().await.unwrap();
+
+    for (id, reason) in [(1u32, 1u32), (2, 2), (3, 3), (4, 4)] {
+        server
+            .emit_signal(
+                None::<&str>,
+                "/org/freedesktop/Notifications",
+                "org.freedesktop.Notifications",
+                "NotificationClosed",
+                &(id, reason),
+            )
+            .await
+            .unwrap();
+    }
+
+    let responses: Vec<_> = closed.by_ref().take(4).collect().await;
+    let ids: Vec<_> = responses
+        .iter()
+        .map(|response| response.notification_id.as_str())
+        .collect();
+    assert_eq!(ids, ["1", "2", "3", "4"]);
+    let actions: Vec<_> = responses
+        .into_iter()
+        .map(|response| response.action)
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            NotifyResponseAction::Expired,
+            NotifyResponseAction::Dismiss,
+            NotifyResponseAction::Hidden,
+            NotifyResponseAction::Hidden,
+        ]
+    );
+}
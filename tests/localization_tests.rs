@@ -0,0 +1,141 @@
+use std::{collections::HashMap, sync::Arc};
+
+use user_notify_reborn::localization::{
+    localize_categories, FileLocalizer, Localization, Localizer,
+};
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::template::TemplateRegistry;
+
+const DE: &str = "
+# Deutsch
+build_finished.title = Build {build} abgeschlossen
+build_finished.body =
+    {project} hat alle Prüfungen bestanden.
+    Details im Dashboard.
+build.open.title = Öffnen
+build.reply.input_placeholder = Nachricht
+";
+
+const EN: &str = "
+build.reply.title = Reply
+greeting = Hello
+";
+
+fn localizer() -> FileLocalizer {
+    let mut localizer = FileLocalizer::new().fallback_locale("en");
+    localizer.add_resource("de", DE).unwrap();
+    localizer.add_resource("en", EN).unwrap();
+    localizer
+}
+
+fn categories() -> Vec<NotifyCategory> {
+    vec![NotifyCategory {
+        identifier: "build".to_string(),
+        actions: vec![
+            NotifyCategoryAction::Action {
+                identifier: "open".to_string(),
+                title: "Open".to_string(),
+            },
+            NotifyCategoryAction::TextInputAction {
+                identifier: "reply".to_string(),
+                title: "Answer".to_string(),
+                input_button_title: "Send".to_string(),
+                input_placeholder: "Message".to_string(),
+            },
+        ],
+        ..Default::default()
+    }]
+}
+
+#[test]
+fn test_file_localizer_fallbacks() {
+    let localizer = localizer();
+
+    assert_eq!(
+        localizer.translate("de-AT", "build.open.title").as_deref(),
+        Some("Öffnen")
+    );
+    assert_eq!(
+        localizer.translate("de", "greeting").as_deref(),
+        Some("Hello")
+    );
+    assert_eq!(
+        localizer.translate("de", "build_finished.body").as_deref(),
+        Some("{project} hat alle Prüfungen bestanden.\nDetails im Dashboard.")
+    );
+    assert_eq!(localizer.translate("fr", "missing"), None);
+}
+
+#[test]
+fn test_file_localizer_rejects_invalid_resource() {
+    let mut localizer = FileLocalizer::new();
+
+    assert!(matches!(
+        localizer.add_resource("de", "no separator"),
+        Err(NotifyError::LocalizationParse(_))
+    ));
+    assert!(matches!(
+        localizer.add_resource("de", "  continuation"),
+        Err(NotifyError::LocalizationParse(_))
+    ));
+    assert!(localizer.locales().is_empty());
+}
+
+/// Returns the open title and the reply title, button title and placeholder of the category
+fn action_texts(categories: &[NotifyCategory]) -> [&str; 4] {
+    match categories[0].actions.as_slice() {
+        [NotifyCategoryAction::Action { title, .. }, NotifyCategoryAction::TextInputAction {
+            title: reply_title,
+            input_button_title,
+            input_placeholder,
+            ..
+        }] => [title, reply_title, input_button_title, input_placeholder],
+        actions => panic!("unexpected actions {actions:?}"),
+    }
+}
+
+#[test]
+fn test_localize_categories() {
+    let categories = localize_categories(&categories(), &localizer(), "de");
+
+    // falls back to the English translation and then to the defined text
+    assert_eq!(
+        action_texts(&categories),
+        ["Öffnen", "Reply", "Send", "Nachricht"]
+    );
+}
+
+#[test]
+fn test_render_localized_template() {
+    let mut templates = TemplateRegistry::new(&categories());
+    templates
+        .load_toml(
+            r#"
+            [templates.build_finished]
+            title = "Build {build} finished"
+            subtitle = "{project}"
+            body = "{project} passed all checks"
+            category = "build"
+            variables = ["build", "project"]
+            "#,
+        )
+        .unwrap();
+    let vars = HashMap::from([
+        ("build".to_string(), "4711".to_string()),
+        ("project".to_string(), "gety".to_string()),
+    ]);
+
+    let localization = Localization::new(Arc::new(localizer()), "de", categories());
+    let builder = localization
+        .render(&templates, "build_finished", &vars)
+        .unwrap();
+
+    assert_eq!(builder.get_title(), Some("Build 4711 abgeschlossen"));
+    assert_eq!(
+        builder.get_body(),
+        Some("gety hat alle Prüfungen bestanden.\nDetails im Dashboard.")
+    );
+    assert_eq!(builder.get_subtitle(), Some("gety"));
+    assert_eq!(localization.locale(), "de");
+    assert_eq!(action_texts(&localization.categories())[0], "Öffnen");
+}
@@ -0,0 +1,139 @@
+use url::Url;
+use user_notify_reborn::markup::{BodyMarkup, MarkupSpan};
+use user_notify_reborn::prelude::*;
+
+const BODY: &str = r"**Build 4711** *failed* on <main> & [see logs](https://ci.example.com/4711?tab=logs&line=1) \*";
+
+#[test]
+fn test_parse_markup() {
+    let markup = BodyMarkup::parse("**bold *both*** and [*link*](https://example.com)").unwrap();
+
+    assert_eq!(
+        markup.spans(),
+        [
+            MarkupSpan::Bold(vec![
+                MarkupSpan::Text("bold ".to_string()),
+                MarkupSpan::Italic(vec![MarkupSpan::Text("both".to_string())]),
+            ]),
+            MarkupSpan::Text(" and ".to_string()),
+            MarkupSpan::Link {
+                label: vec![MarkupSpan::Italic(vec![MarkupSpan::Text(
+                    "link".to_string()
+                )])],
+                url: Url::parse("https://example.com").unwrap(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_markup_to_plain_text() {
+    let markup = BodyMarkup::parse(BODY).unwrap();
+
+    assert_eq!(
+        markup.to_plain_text(),
+        "Build 4711 failed on <main> & see logs *"
+    );
+}
+
+#[test]
+fn test_markup_to_freedesktop_markup() {
+    let markup = BodyMarkup::parse(BODY).unwrap();
+
+    assert_eq!(
+        markup.render_for_capabilities(&["body", "body-markup", "body-hyperlinks"]),
+        "<b>Build 4711</b> <i>failed</i> on &lt;main&gt; &amp; \
+         <a href=\"https://ci.example.com/4711?tab=logs&amp;line=1\">see logs</a> *"
+    );
+    assert_eq!(
+        markup.render_for_capabilities(&["body-markup"]),
+        "<b>Build 4711</b> <i>failed</i> on &lt;main&gt; &amp; see logs *"
+    );
+    assert_eq!(
+        markup.render_for_capabilities(&["body"]),
+        markup.to_plain_text()
+    );
+}
+
+#[test]
+fn test_unmatched_delimiters_are_text() {
+    for (source, text) in [
+        ("Rated 5*", "Rated 5*"),
+        ("Rated 5* and 4* stars", "Rated 5* and 4* stars"),
+        ("5 * 3 = 15", "5 * 3 = 15"),
+        ("**unclosed", "**unclosed"),
+        ("*unclosed **bold**", "*unclosed bold"),
+        ("[label]", "[label]"),
+        ("see [1] and *this*", "see [1] and this"),
+        ("closing ]", "closing ]"),
+    ] {
+        let markup = BodyMarkup::parse(source).unwrap();
+        assert_eq!(markup.to_plain_text(), text, "{source}");
+    }
+
+    assert_eq!(
+        BodyMarkup::parse("Rated 5* by *Ada*").unwrap().spans(),
+        [
+            MarkupSpan::Text("Rated 5* by ".to_string()),
+            MarkupSpan::Italic(vec![MarkupSpan::Text("Ada".to_string())]),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_markup() {
+    for source in [
+        "*a **b *c*** d*",
+        "[label](https://example.com",
+        "[[nested](a)](b)",
+        "[relative](/path)",
+    ] {
+        assert!(
+            matches!(
+                BodyMarkup::parse(source),
+                Err(NotifyError::InvalidMarkup(_))
+            ),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn test_builder_body_markup() {
+    let builder = NotifyBuilder::new()
+        .body_markup("**Build 4711** finished")
+        .unwrap();
+    assert_eq!(
+        builder
+            .preview(user_notify_reborn::truncation::Platform::MacOS)
+            .body
+            .as_deref(),
+        Some("Build 4711 finished")
+    );
+
+    assert!(NotifyBuilder::new()
+        .body_markup("[relative](/path)")
+        .is_err());
+}
+
+#[test]
+fn test_markup_links() {
+    let markup = BodyMarkup::parse(
+        "[View PR **#123**](https://example.com/pr/123) or [CI](https://ci.example.com)",
+    )
+    .unwrap();
+
+    assert_eq!(
+        markup.links(),
+        [
+            (
+                "View PR #123".to_string(),
+                Url::parse("https://example.com/pr/123").unwrap()
+            ),
+            (
+                "CI".to_string(),
+                Url::parse("https://ci.example.com").unwrap()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{temp_path, MockNotifyManager};
+use user_notify_reborn::history::{HistoryNotifyManager, NotificationHistory};
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::{validate_notification_id, Platform};
+
+#[test]
+fn test_valid_notification_ids() {
+    for platform in [Platform::Windows, Platform::MacOS, Platform::Linux] {
+        for id in ["build-4711", "chat:42.msg_7", &"a".repeat(64)] {
+            assert!(
+                validate_notification_id(id, platform).is_ok(),
+                "{id} on {platform:?}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_send_with_notification_id() {
+    let path = temp_path("history.jsonl");
+    let manager = HistoryNotifyManager::new(
+        MockNotifyManager::default(),
+        NotificationHistory::open(&path).unwrap(),
+    );
+
+    let builder = NotifyBuilder::new().id("build-4711").unwrap();
+    assert_eq!(builder.get_id(), Some("build-4711"));
+    let handle = manager.send(builder).await.unwrap();
+
+    assert_eq!(handle.get_id(), "build-4711");
+    assert!(manager.history().get("build-4711").unwrap().is_some());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_invalid_notification_ids() {
+    for platform in [Platform::Windows, Platform::MacOS, Platform::Linux] {
+        for id in ["", "build 4711", "build/4711", "bäd", "line\nbreak"] {
+            assert!(
+                matches!(
+                    validate_notification_id(id, platform),
+                    Err(NotifyError::InvalidNotificationId { .. })
+                ),
+                "{id:?} on {platform:?}"
+            );
+        }
+    }
+
+    assert!(NotifyBuilder::new().id("build 4711").is_err());
+}
+
+#[test]
+fn test_windows_notification_id_length() {
+    let id = "a".repeat(65);
+
+    let err = validate_notification_id(&id, Platform::Windows).unwrap_err();
+    assert!(err.to_string().contains("64 characters"), "{err}");
+    assert!(validate_notification_id(&id, Platform::MacOS).is_ok());
+}
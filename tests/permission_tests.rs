@@ -0,0 +1,31 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockNotifyManager;
+use futures_util::StreamExt;
+use user_notify_reborn::dedupe::{DedupeNotifyManager, DedupePolicy};
+use user_notify_reborn::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn test_permission_changes() {
+    let mock = MockNotifyManager::default();
+    mock.settings.lock().unwrap().state = PermissionState::Authorized;
+    let manager = DedupeNotifyManager::new(mock.clone(), DedupePolicy::suppress(Duration::ZERO));
+    let mut changes = manager.permission_changes().await.unwrap();
+
+    let denied = NotificationSettings {
+        state: PermissionState::Denied,
+        alert: FeatureSetting::Disabled,
+        ..Default::default()
+    };
+    *mock.settings.lock().unwrap() = denied;
+    assert_eq!(changes.next().await, Some(denied));
+
+    // unchanged settings are not repeated
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    mock.settings.lock().unwrap().state = PermissionState::Authorized;
+    let authorized = changes.next().await.unwrap();
+    assert_eq!(authorized.state, PermissionState::Authorized);
+    assert_eq!(authorized.alert, FeatureSetting::Disabled);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use std::{
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::Duration,
+};
+
+use common::MockNotifyManager;
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::quiet_hours::{
+    LocalTime, QuietAction, QuietHoursNotifyHandle, QuietHoursNotifyManager, QuietHoursPolicy,
+    QuietRule, Weekday,
+};
+
+fn friday(hour: u8, minute: u8) -> LocalTime {
+    LocalTime::new(Weekday::Friday, hour, minute)
+}
+
+fn night() -> QuietRule {
+    QuietRule::daily((22, 0), (7, 0))
+}
+
+#[test]
+fn test_quiet_rules() {
+    let night = night();
+    assert!(night.contains(friday(23, 0)));
+    assert!(night.contains(friday(6, 59)));
+    assert!(!night.contains(friday(7, 0)));
+    assert!(!night.contains(friday(12, 0)));
+
+    let weekends = QuietRule::weekends();
+    assert!(!weekends.contains(friday(23, 59)));
+    assert!(weekends.contains(LocalTime::new(Weekday::Saturday, 0, 0)));
+    assert!(weekends.contains(LocalTime::new(Weekday::Sunday, 23, 59)));
+    assert!(!weekends.contains(LocalTime::new(Weekday::Monday, 0, 0)));
+
+    // the night from Sunday to Monday starts on Sunday
+    let sunday_night = QuietRule::new(&[Weekday::Sunday], (22, 0), (7, 0));
+    assert!(sunday_night.contains(LocalTime::new(Weekday::Monday, 6, 59)));
+    assert!(!sunday_night.contains(LocalTime::new(Weekday::Sunday, 6, 59)));
+}
+
+fn manager(
+    mock: &MockNotifyManager,
+    policy: QuietHoursPolicy,
+    now: &Arc<Mutex<LocalTime>>,
+) -> QuietHoursNotifyManager<MockNotifyManager> {
+    let now = now.clone();
+    QuietHoursNotifyManager::new(mock.clone(), policy).with_clock(move || *now.lock().unwrap())
+}
+
+fn message(title: &str, urgency: Urgency) -> NotifyBuilder {
+    NotifyBuilder::new().title(title).urgency(urgency)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_defer_until_quiet_hours_end_as_digest() {
+    let mock = MockNotifyManager::default();
+    let now = Arc::new(Mutex::new(friday(23, 0)));
+    let policy = QuietHoursPolicy::new(QuietAction::Defer)
+        .rule(night())
+        .urgency_action(Urgency::Critical, QuietAction::Deliver)
+        .urgency_action(Urgency::Low, QuietAction::Suppress)
+        .digest(|deferred| {
+            NotifyBuilder::new().title(&format!("{} notifications while away", deferred.len()))
+        });
+    let manager = manager(&mock, policy, &now);
+
+    assert!(manager.is_quiet());
+    let critical = manager
+        .send(message("Server down", Urgency::Critical))
+        .await
+        .unwrap();
+    assert!(matches!(critical, QuietHoursNotifyHandle::Sent(_)));
+    let low = manager
+        .send(message("Tip of the day", Urgency::Low))
+        .await
+        .unwrap();
+    assert!(matches!(low, QuietHoursNotifyHandle::Suppressed(_)));
+    for title in ["Message 1", "Message 2"] {
+        let deferred = manager.send(message(title, Urgency::Normal)).await.unwrap();
+        assert!(matches!(deferred, QuietHoursNotifyHandle::Deferred(_)));
+        assert_eq!(deferred.title(), Some(title));
+    }
+    assert_eq!(mock.sent.lock().unwrap().len(), 1);
+    assert_eq!(manager.deferred_count(), 2);
+
+    tokio::time::sleep(Duration::from_secs(120)).await;
+    assert_eq!(mock.sent.lock().unwrap().len(), 1);
+
+    *now.lock().unwrap() = friday(7, 0);
+    tokio::time::sleep(Duration::from_secs(61)).await;
+
+    assert_eq!(
+        mock.sent_titles(),
+        [
+            Some("Server down".to_string()),
+            Some("2 notifications while away".to_string()),
+        ]
+    );
+    assert_eq!(manager.deferred_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_manual_quiet_hours_downgrade() {
+    let mock = MockNotifyManager::default();
+    let now = Arc::new(Mutex::new(friday(12, 0)));
+    let manager = manager(&mock, QuietHoursPolicy::new(QuietAction::Downgrade), &now);
+
+    let handle = manager
+        .send(message("Before", Urgency::Normal))
+        .await
+        .unwrap();
+    assert!(matches!(handle, QuietHoursNotifyHandle::Sent(_)));
+
+    manager.set_manual(true).unwrap();
+    let handle = manager
+        .send(message("Presenting", Urgency::Critical))
+        .await
+        .unwrap();
+    assert!(matches!(handle, QuietHoursNotifyHandle::Downgraded(_)));
+
+    let urgencies: Vec<_> = mock
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, builder)| builder.get_urgency())
+        .collect();
+    assert_eq!(urgencies, [Some(Urgency::Normal), Some(Urgency::Low)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_close_deferred_and_release() {
+    let mock = MockNotifyManager::default();
+    let now = Arc::new(Mutex::new(friday(23, 0)));
+    let manager = manager(
+        &mock,
+        QuietHoursPolicy::new(QuietAction::Defer).rule(night()),
+        &now,
+    );
+
+    let closed = manager
+        .send(message("Closed", Urgency::Normal))
+        .await
+        .unwrap();
+    let handle = manager
+        .send(message("Kept", Urgency::Normal))
+        .await
+        .unwrap();
+    closed.close().unwrap();
+
+    manager.release().await.unwrap();
+
+    assert_eq!(mock.sent_titles(), [Some("Kept".to_string())]);
+    assert_eq!(mock.sent_ids(), [handle.get_id()]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_release_keeps_notifications_deferred() {
+    let mock = MockNotifyManager::default();
+    let now = Arc::new(Mutex::new(friday(23, 0)));
+    let manager = manager(
+        &mock,
+        QuietHoursPolicy::new(QuietAction::Defer).rule(night()),
+        &now,
+    );
+    for title in ["Message 1", "Message 2"] {
+        manager.send(message(title, Urgency::Normal)).await.unwrap();
+    }
+
+    mock.fail_sends.store(true, Ordering::SeqCst);
+    assert!(manager.release().await.is_err());
+    assert_eq!(manager.deferred_count(), 2);
+
+    // the release when quiet hours end is retried at the next check
+    *now.lock().unwrap() = friday(7, 0);
+    tokio::time::sleep(Duration::from_secs(61)).await;
+    assert_eq!(manager.deferred_count(), 2);
+
+    mock.fail_sends.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(
+        mock.sent_titles(),
+        [Some("Message 1".to_string()), Some("Message 2".to_string())]
+    );
+    assert_eq!(manager.deferred_count(), 0);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "serde")]
+
+use serde_json::json;
+use user_notify_reborn::prelude::*;
+
+#[test]
+fn test_builder_serialization() {
+    let builder = NotifyBuilder::new()
+        .title("Build finished")
+        .body("build-4711 passed")
+        .set_category_id("build")
+        .set_metadata(&json!({ "build": 4711 }))
+        .unwrap();
+
+    let value = serde_json::to_value(&builder).unwrap();
+    assert_eq!(value["title"], "Build finished");
+    assert_eq!(value["category_id"], "build");
+    assert_eq!(value["user_metadata"], json!({ "build": 4711 }));
+
+    let partial: NotifyBuilder = serde_json::from_value(json!({ "title": "Hello" })).unwrap();
+    assert_eq!(serde_json::to_value(partial).unwrap()["title"], "Hello");
+}
+
+#[test]
+fn test_category_serialization() {
+    let category: NotifyCategory = serde_json::from_value(json!({
+        "identifier": "chat",
+        "actions": [
+            { "type": "action", "identifier": "open", "title": "Open" },
+            {
+                "type": "text_input_action",
+                "identifier": "reply",
+                "title": "Reply",
+                "input_button_title": "Send",
+                "input_placeholder": "Message"
+            }
+        ],
+        "send_dismiss_action": true
+    }))
+    .unwrap();
+
+    assert_eq!(category.identifier, "chat");
+    assert_eq!(category.actions.len(), 2);
+    assert!(category.send_dismiss_action);
+    assert!(category.summary_format.is_none());
+    assert!(matches!(
+        category.actions[1],
+        NotifyCategoryAction::TextInputAction { ref input_button_title, .. } if input_button_title == "Send"
+    ));
+}
+
+#[test]
+fn test_response_serialization() {
+    let response = NotifyResponse {
+        notification_id: "0123456789abcdef".to_string(),
+        action: NotifyResponseAction::Other("reply".to_string()),
+        user_input: Some("on my way".to_string()),
+        user_metadata: json!({ "chat": 42 }),
+    };
+
+    let value = serde_json::to_value(&response).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "notification_id": "0123456789abcdef",
+            "action": { "other": "reply" },
+            "user_input": "on my way",
+            "user_metadata": { "chat": 42 }
+        })
+    );
+
+    let decoded: NotifyResponse = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded.action, response.action);
+
+    assert_eq!(
+        serde_json::to_value(NotifyResponseAction::Default).unwrap(),
+        json!("default")
+    );
+    assert_eq!(
+        serde_json::to_value(NotifyResponseAction::Dismiss).unwrap(),
+        json!("dismiss")
+    );
+    assert_eq!(
+        serde_json::to_value(NotifyResponseAction::Expired).unwrap(),
+        json!("expired")
+    );
+    assert_eq!(
+        serde_json::to_value(NotifyResponseAction::OpenUrl(
+            url::Url::parse("https://example.com/pr/123").unwrap()
+        ))
+        .unwrap(),
+        json!({ "open_url": "https://example.com/pr/123" })
+    );
+}
+
+#[test]
+fn test_notification_settings_serialization() {
+    let settings = NotificationSettings {
+        state: PermissionState::Provisional,
+        alert: FeatureSetting::Enabled,
+        sound: FeatureSetting::Disabled,
+        ..Default::default()
+    };
+    assert!(settings.state.is_granted());
+
+    let value = serde_json::to_value(settings).unwrap();
+    assert_eq!(value["state"], "provisional");
+    assert_eq!(value["sound"], "disabled");
+    assert_eq!(value["badge"], "not_supported");
+    assert_eq!(value["disabled_by"], serde_json::Value::Null);
+    assert_eq!(
+        serde_json::from_value::<NotificationSettings>(value).unwrap(),
+        settings
+    );
+}
+
+#[test]
+fn test_presentation_serialization() {
+    let builder = NotifyBuilder::new().presentation(PresentationOptions::none());
+    let value = serde_json::to_value(&builder).unwrap();
+    assert_eq!(
+        value["presentation"],
+        json!({ "banner": false, "list": false, "sound": false, "badge": false })
+    );
+
+    let presentation: PresentationOptions =
+        serde_json::from_value(json!({ "banner": false })).unwrap();
+    assert_eq!(
+        presentation,
+        PresentationOptions {
+            banner: false,
+            ..Default::default()
+        }
+    );
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::template::TemplateRegistry;
+
+const TEMPLATES: &str = r#"
+[templates.build_finished]
+title = "Build {build} finished"
+body = "{project} passed all checks {{ok}}"
+category = "build"
+urgency = "low"
+thread_id = "builds.{project}"
+variables = ["build", "project"]
+
+[templates.plain]
+title = "Hello"
+"#;
+
+fn registry() -> TemplateRegistry {
+    TemplateRegistry::new(&[NotifyCategory {
+        identifier: "build".to_string(),
+        ..Default::default()
+    }])
+}
+
+fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_render_toml_template() {
+    let mut templates = registry();
+    templates.load_toml(TEMPLATES).unwrap();
+
+    let builder = templates
+        .render(
+            "build_finished",
+            &vars(&[("build", "4711"), ("project", "gety")]),
+        )
+        .unwrap();
+
+    assert_eq!(builder.get_title(), Some("Build 4711 finished"));
+    assert_eq!(builder.get_body(), Some("gety passed all checks {ok}"));
+    assert_eq!(builder.get_thread_id(), Some("builds.gety"));
+    assert_eq!(builder.get_category_id(), Some("build"));
+    assert_eq!(builder.get_urgency(), Some(Urgency::Low));
+    assert!(templates.contains("plain"));
+}
+
+#[test]
+fn test_render_json_template() {
+    let mut templates = registry();
+    templates
+        .load_json(
+            r#"{ "templates": { "greeting": { "title": "Hi {name}", "variables": ["name"] } } }"#,
+        )
+        .unwrap();
+
+    let builder = templates
+        .render("greeting", &vars(&[("name", "Ada")]))
+        .unwrap();
+    assert_eq!(builder.get_title(), Some("Hi Ada"));
+    assert_eq!(builder.get_body(), None);
+}
+
+#[test]
+fn test_render_missing_variable() {
+    let mut templates = registry();
+    templates.load_toml(TEMPLATES).unwrap();
+
+    assert!(matches!(
+        templates.render("build_finished", &vars(&[("build", "4711")])),
+        Err(NotifyError::MissingTemplateVariable { ref variable, .. }) if variable == "project"
+    ));
+    assert!(matches!(
+        templates.render("unknown", &HashMap::new()),
+        Err(NotifyError::TemplateNotFound(_))
+    ));
+}
+
+#[test]
+fn test_load_rejects_invalid_templates() {
+    let invalid = [
+        "[templates.t]\ncategory = \"unknown\"",
+        "[templates.t]\ntitle = \"{a}\"\nvariables = []",
+        "[templates.t]\ntitle = \"Hi {usre}\"\nvariables = [\"user\"]",
+        "[templates.t]\ntitle = \"{unclosed\"",
+        "[templates.t]\nurgency = \"urgent\"",
+    ];
+    for source in invalid {
+        assert!(
+            matches!(
+                registry().load_toml(source),
+                Err(NotifyError::InvalidTemplate { .. })
+            ),
+            "{source}"
+        );
+    }
+
+    assert!(matches!(
+        registry().load_toml("[templates.t]\nbody_text = \"x\""),
+        Err(NotifyError::TemplateParse(_))
+    ));
+}
+
+#[test]
+fn test_load_rejects_placeholders_without_variables() {
+    let result = registry().load_toml("[templates.t]\ntitle = \"Hi {usre}\"");
+
+    assert!(matches!(
+        result,
+        Err(NotifyError::InvalidTemplate { ref reason, .. }) if reason.contains("usre")
+    ));
+}
+
+#[test]
+fn test_load_is_atomic() {
+    let mut templates = registry();
+    let result = templates.load_toml(
+        "[templates.valid]\ntitle = \"ok\"\n\n[templates.invalid]\ncategory = \"unknown\"",
+    );
+
+    assert!(result.is_err());
+    assert!(!templates.contains("valid"));
+}
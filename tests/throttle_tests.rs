@@ -0,0 +1,115 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockNotifyManager;
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::throttle::{RateLimit, ThrottledNotifyManager};
+
+fn message(thread: &str, n: usize) -> NotifyBuilder {
+    NotifyBuilder::new()
+        .title(&format!("Message {n}"))
+        .set_category_id("chat")
+        .set_thread_id(thread)
+}
+
+fn manager(
+    mock: &MockNotifyManager,
+    limit: RateLimit,
+) -> ThrottledNotifyManager<MockNotifyManager> {
+    ThrottledNotifyManager::new(mock.clone(), limit, |burst| {
+        NotifyBuilder::new()
+            .title(&format!("{} new messages", burst.notifications.len()))
+            .set_category_id(burst.key.category_id.as_deref().unwrap_or_default())
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_burst_is_coalesced_into_summary() {
+    let mock = MockNotifyManager::default();
+    let manager = manager(&mock, RateLimit::new(2, Duration::from_secs(10)));
+
+    let mut handles = Vec::new();
+    for n in 0..5 {
+        handles.push(manager.send(message("room", n)).await.unwrap());
+    }
+    assert_eq!(
+        handles
+            .iter()
+            .map(|handle| handle.is_pending())
+            .collect::<Vec<_>>(),
+        [false, false, true, true, true]
+    );
+    assert_eq!(mock.sent.lock().unwrap().len(), 2);
+    assert_eq!(manager.pending_count(), 3);
+    assert_eq!(handles[4].title(), Some("Message 4"));
+    assert_eq!(handles[4].delivered_at(), None);
+
+    tokio::time::sleep(Duration::from_secs(11)).await;
+
+    assert_eq!(
+        mock.sent_titles(),
+        [
+            Some("Message 0".to_string()),
+            Some("Message 1".to_string()),
+            Some("3 new messages".to_string()),
+        ]
+    );
+    assert_eq!(manager.pending_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_single_held_back_notification_is_sent_unchanged() {
+    let mock = MockNotifyManager::default();
+    let manager = manager(&mock, RateLimit::new(1, Duration::from_secs(10)));
+
+    manager.send(message("room", 0)).await.unwrap();
+    let handle = manager.send(message("room", 1)).await.unwrap();
+    assert!(handle.is_pending());
+
+    tokio::time::sleep(Duration::from_secs(11)).await;
+
+    assert_eq!(
+        mock.sent_titles(),
+        [Some("Message 0".to_string()), Some("Message 1".to_string())]
+    );
+    assert_eq!(mock.sent_ids()[1], handle.get_id());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_threads_and_categories_have_own_buckets() {
+    let mock = MockNotifyManager::default();
+    let manager = manager(&mock, RateLimit::new(1, Duration::from_secs(10)))
+        .with_category_limit("build", RateLimit::new(3, Duration::from_secs(10)));
+
+    assert!(!manager.send(message("a", 0)).await.unwrap().is_pending());
+    assert!(!manager.send(message("b", 0)).await.unwrap().is_pending());
+    assert!(manager.send(message("a", 1)).await.unwrap().is_pending());
+
+    for n in 0..3 {
+        let build = NotifyBuilder::new()
+            .title(&format!("Build {n}"))
+            .set_category_id("build");
+        assert!(!manager.send(build).await.unwrap().is_pending());
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_close_and_flush_held_back_notifications() {
+    let mock = MockNotifyManager::default();
+    let manager = manager(&mock, RateLimit::new(1, Duration::from_secs(60)));
+
+    manager.send(message("room", 0)).await.unwrap();
+    let closed = manager.send(message("room", 1)).await.unwrap();
+    manager.send(message("room", 2)).await.unwrap();
+    closed.close().unwrap();
+    assert_eq!(manager.pending_count(), 1);
+
+    manager.flush().await.unwrap();
+
+    assert_eq!(
+        mock.sent_titles(),
+        [Some("Message 0".to_string()), Some("Message 2".to_string())]
+    );
+    assert_eq!(manager.pending_count(), 0);
+}
@@ -0,0 +1,115 @@
+use user_notify_reborn::prelude::*;
+use user_notify_reborn::truncation::{
+    truncate_text, LineLimit, NotifyPreview, Platform, TextLimits, Truncation,
+};
+
+const LONG_BODY: &str = "The nightly build of the desktop app finished after 42 minutes. \
+    All 1234 tests passed, 3 were skipped, and the installer was uploaded to the release \
+    channel. Coverage went up by 0.4 percent compared to yesterday's build.";
+
+#[test]
+fn test_short_text_is_kept() {
+    let limit = LineLimit {
+        lines: 1,
+        width: 20,
+    };
+    assert_eq!(
+        truncate_text("Build finished", limit, "…"),
+        ("Build finished".to_string(), false)
+    );
+}
+
+#[test]
+fn test_truncate_between_words() {
+    let limit = LineLimit {
+        lines: 1,
+        width: 20,
+    };
+    let (text, truncated) = truncate_text("The nightly build finished successfully", limit, "…");
+
+    assert!(truncated);
+    assert_eq!(text, "The nightly build…");
+}
+
+#[test]
+fn test_truncate_is_grapheme_safe() {
+    let limit = LineLimit { lines: 1, width: 4 };
+    // family emoji and combining accents are single graphemes of several chars
+    let (text, truncated) = truncate_text("👨‍👩‍👧e\u{301}e\u{301}e\u{301}e\u{301}", limit, "…");
+
+    assert!(truncated);
+    assert_eq!(text, "👨‍👩‍👧e\u{301}e\u{301}…");
+}
+
+#[test]
+fn test_truncate_counts_line_breaks() {
+    let limit = LineLimit {
+        lines: 2,
+        width: 40,
+    };
+    let (text, truncated) = truncate_text("first\nsecond\nthird", limit, "...");
+
+    assert!(truncated);
+    assert_eq!(text, "first\nsecond...");
+}
+
+#[test]
+fn test_preview_per_platform() {
+    let builder = NotifyBuilder::new()
+        .title("Nightly build of the desktop app finished")
+        .subtitle("Release channel")
+        .body(LONG_BODY);
+
+    let macos = builder.preview(Platform::MacOS);
+    assert!(macos.truncated);
+    assert_eq!(
+        macos.title.as_deref(),
+        Some("Nightly build of the desktop app…")
+    );
+    assert_eq!(macos.subtitle.as_deref(), Some("Release channel"));
+    // the subtitle takes one of the four lines of the body
+    let body = macos.body.unwrap();
+    assert!(body.ends_with('…'));
+    assert!(body.chars().count() <= 3 * 40, "{body}");
+
+    let windows = builder.preview(Platform::Windows);
+    assert_eq!(
+        windows.title.as_deref(),
+        Some("Nightly build of the desktop app finished")
+    );
+    assert!(windows.body.unwrap().chars().count() > body.chars().count());
+}
+
+#[test]
+fn test_preview_with_custom_truncation() {
+    let limits = TextLimits {
+        title: LineLimit {
+            lines: 1,
+            width: 10,
+        },
+        subtitle: LineLimit {
+            lines: 1,
+            width: 10,
+        },
+        body: LineLimit {
+            lines: 1,
+            width: 10,
+        },
+        subtitle_shares_body_lines: true,
+    };
+    let builder = NotifyBuilder::new()
+        .title("Build 4711 finished")
+        .subtitle("Release")
+        .body("passed")
+        .truncation(Truncation::default().ellipsis(" [..]").limits(limits));
+
+    assert_eq!(
+        builder.preview(Platform::Linux),
+        NotifyPreview {
+            title: Some("Build [..]".to_string()),
+            subtitle: Some("Release".to_string()),
+            body: None,
+            truncated: true,
+        }
+    );
+}